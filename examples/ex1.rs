@@ -1,13 +1,6 @@
 use bevy::{
-    asset::AssetLoadFailedEvent,
-    ecs::{
-        entity::MapEntities, reflect::ReflectMapEntities, resource::IsResource, system::SystemId,
-    },
-    prelude::*,
-    tasks::IoTaskPool,
-    window::RequestRedraw,
+    ecs::system::SystemId, prelude::*, tasks::IoTaskPool, window::RequestRedraw,
     winit::WinitSettings,
-    world_serialization::DynamicWorld,
 };
 use bevy_egui::{
     egui::{self, Direction},
@@ -47,22 +40,12 @@ impl std::fmt::Display for Flags {
     }
 }
 
-// FIX? The migration guide explicitly mentions that it is no longer necessary to add derive MapEntities for a resource (<https://bevy.org/learn/migration-guides/0-18-to-0-19/#miscellaneous>)...
+// The roots that are saved. They're not saved themselves, `Persist::load` returns them in the same order.
+#[derive(Debug, Resource)]
+struct PaperDoll(Entity);
 
-// These need to be reflectable to be written to the contents scene, as well as the type registered. An alternative would be to show windows for all root level contents that aren't items. Or add a separate marker component(s).
-#[derive(Debug, Resource, MapEntities, Reflect)]
-#[reflect(Debug, Resource, MapEntities)]
-struct PaperDoll(#[entities] Entity);
-
-#[derive(Debug, Resource, MapEntities, Reflect)]
-#[reflect(Debug, Resource, MapEntities)]
-struct Ground(#[entities] Entity);
-
-// Remembers which containers are opened.
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-#[component(storage = "SparseSet")]
-struct Open;
+#[derive(Debug, Resource)]
+struct Ground(Entity);
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 enum AppState {
@@ -74,23 +57,35 @@ enum AppState {
 fn main() {
     App::new()
         .insert_resource(WinitSettings::default())
-        .register_type::<PaperDoll>()
-        .register_type::<Ground>()
+        // Hold a dragged item over a container item to open it, preview
+        // container contents when hovering them, summarize containers in
+        // their headers, and tint containers by whether a dragged item fits.
+        .insert_resource(Options {
+            spring_delay: Some(0.6),
+            hover_preview: Some(0.5),
+            summary: Summary {
+                text: true,
+                bar: true,
+                fits: true,
+            },
+            drop_highlight: Some(DropHighlight::default()),
+            ..default()
+        })
         .add_plugins((DefaultPlugins, RunicPlugin::<Flags>::default()))
         .init_state::<AppState>()
         .add_plugins(EguiPlugin::default())
         .add_systems(Startup, startup)
         .add_systems(OnEnter(AppState::Loading), load_items)
-        .add_systems(Update, wait_for_items.run_if(in_state(AppState::Loading)))
+        // If nothing was loaded, spawn new items.
         .add_systems(
             Update,
             spawn_items
                 .run_if(in_state(AppState::Loading))
-                .run_if(on_message::<AssetLoadFailedEvent<DynamicWorld>>),
+                .run_if(not(resource_exists::<Ground>)),
         )
         .add_systems(
             EguiPrimaryContextPass,
-            (item_icon_changed::<Flags>, update)
+            (item_icon_changed, update)
                 .chain()
                 .run_if(in_state(AppState::Running)),
         )
@@ -215,8 +210,6 @@ fn container_open(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
 ) {
-    commands.entity(event.event_target()).insert(Open);
-
     commands
         .entity(event.event_target())
         .insert(AudioPlayer::new(asset_server.load("sfx100v2_wood_03.ogg")))
@@ -235,30 +228,39 @@ fn redraw(mut events: MessageReader<AssetEvent<Image>>, mut redraw: MessageWrite
 #[derive(Resource)]
 struct SaveItems(SystemId);
 
-const CONTENTS_FILE_PATH: &str = "contents.scn.ron";
-
-fn load_items(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let id = commands.register_system(save_items_scene);
-    commands.insert_resource(SaveItems(id));
+const CONTENTS_FILE_PATH: &str = "assets/contents.ron";
 
-    commands.spawn((
-        Name::new("contents scene"),
-        DynamicWorldRoot(asset_server.load(CONTENTS_FILE_PATH)),
-    ));
+fn persist() -> Persist<Flags> {
+    Persist::ron()
+        .deny_component::<AudioPlayer>()
+        .deny_component::<PlaybackSettings>()
+        // Items that no longer fit after migrating go on the ground.
+        .with_overflow(|roots| roots[0])
 }
 
-fn wait_for_items(
-    mut asset_events: MessageReader<AssetEvent<DynamicWorld>>,
-    mut next_state: ResMut<NextState<AppState>>,
-) {
-    for event in asset_events.read() {
-        match event {
-            AssetEvent::LoadedWithDependencies { id: _ } => {
-                info!("contents loaded!");
-                next_state.set(AppState::Running);
-            }
-            _ => warn!(?event),
+fn load_items(world: &mut World) {
+    let id = world.register_system(save_items_scene);
+    world.insert_resource(SaveItems(id));
+
+    let data = match std::fs::read(CONTENTS_FILE_PATH) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("error reading {CONTENTS_FILE_PATH}: {e}");
+            return;
         }
+    };
+
+    match persist().load(world, &data).as_deref() {
+        Ok(&[ground, paper_doll]) => {
+            info!("contents loaded!");
+            world.insert_resource(Ground(ground));
+            world.insert_resource(PaperDoll(paper_doll));
+            world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::Running);
+        }
+        Ok(roots) => warn!("expected two roots, got {}", roots.len()),
+        Err(e) => warn!("error loading contents: {e}"),
     }
 }
 
@@ -276,36 +278,18 @@ fn save_items(
 }
 
 fn save_items_scene(world: &mut World) {
-    let mut query =
-        world.query_filtered::<Entity, Or<(With<Item<Flags>>, With<ContentsItems<Flags>>)>>();
-    let type_registry = world.resource::<AppTypeRegistry>().read();
-    let scene = DynamicWorldBuilder::from_world(&world, &type_registry)
-        // .deny_all_resources()
-        .allow_resource::<Ground>()
-        .allow_resource::<PaperDoll>()
-        .deny_component::<PlaybackSettings>()
-        .extract_resources()
-        .extract_entities(query.iter(&world))
-        .build();
-
-    assert!(!scene.resources.is_empty());
-
-    let type_registry = world.resource::<AppTypeRegistry>();
-    let type_registry = type_registry.read();
-    let serialized_scene = scene
-        .serialize(&type_registry)
-        .expect("error serializing scene!");
-
-    // info!("{}", serialized_scene);
+    let roots = [
+        world.resource::<Ground>().0,
+        world.resource::<PaperDoll>().0,
+    ];
+    let data = persist()
+        .save(world, &roots)
+        .expect("error saving contents!");
 
     #[cfg(not(target_arch = "wasm32"))]
     IoTaskPool::get()
         .spawn(async move {
-            std::fs::write(
-                format!("assets/{CONTENTS_FILE_PATH}"),
-                serialized_scene.as_bytes(),
-            )
-            .expect("error writing contents to file");
+            std::fs::write(CONTENTS_FILE_PATH, data).expect("error writing contents to file");
         })
         .detach();
 }
@@ -326,14 +310,15 @@ fn spawn_items(
             .builder()
             .with_name("Ground".into())
             .with_items([
-                ContentsBuilder::item(
-                    Item::new(Flags::Weapon).with_shape(Shape::from_ones(2, [1, 1, 1, 0])),
-                )
-                .with_icon(asset_server.load("boomerang.png"))
-                .with_name("Boomerang".into()),
+                ContentsBuilder::item(Item::new(Flags::Weapon).with_shape(shape!["##", "#."]))
+                    .with_icon(asset_server.load("boomerang.png"))
+                    .with_name("Boomerang".into())
+                    .with_weight(1.5),
                 ContentsBuilder::item(Item::new(Flags::Container).with_shape((2, 2)))
                     .with_icon(asset_server.load("pouch.png"))
                     .with_name("Pouch".into())
+                    .with_weight(0.5)
+                    .with_max_weight(3.0)
                     .with_contents(
                         GridContents::<_>::new((3, 2)).with_header("Any:"), // .with_flags(Flags::Container),
                     )
@@ -353,13 +338,16 @@ fn spawn_items(
                         .with_rotation(ItemRotation::R90),
                 )
                 .with_icon(asset_server.load("short-sword.png"))
-                .with_name(Name::from("Short sword")),
+                .with_name(Name::from("Short sword"))
+                .with_weight(2.5),
                 ContentsBuilder::item(Item::new(Flags::Potion).with_shape((1, 1)))
                     .with_icon(asset_server.load("potion.png"))
-                    .with_name(Name::from("Potion 1")),
+                    .with_name(Name::from("Potion 1"))
+                    .with_weight(0.5),
                 ContentsBuilder::item(Item::new(Flags::Potion).with_shape((1, 1)))
                     .with_icon(asset_server.load("potion.png"))
-                    .with_name(Name::from("Potion 2")),
+                    .with_name(Name::from("Potion 2"))
+                    .with_weight(0.5),
                 // ContentsBuilder::item(
                 //     Item::new(Flags::TradeGood)
                 //         .with_icon(textures.add_image(asset_server.load("artifact.png")))
@@ -383,26 +371,37 @@ fn spawn_items(
         GridContents::<_>::new((2, 2))
             .with_header("Only potions! 2x2:")
             .with_flags(Flags::Potion)
-            .builder(),
+            .builder()
+            .into_kind(),
         GridContents::<_>::new((3, 2))
             .with_expands(true)
             .with_header("Weapon (3x2 MAX):")
             .with_flags(Flags::Weapon)
-            .builder(),
+            .builder()
+            .into_kind(),
+        // Different kinds of contents can be mixed once converted with `into_kind`.
+        ListContents::<_>::new(4)
+            .with_header("Belt:")
+            .with_flags(Flags::Potion)
+            .builder()
+            .into_kind(),
         GridContents::<_>::new((2, 2))
             .with_header("Holds a container:")
             .with_expands(true)
             .with_inline(true)
             .with_flags(Flags::Container)
             .builder()
-            .with_sections(sub_sections),
+            .with_sections(sub_sections)
+            .into_kind(),
     ];
 
     let paper_doll = storage.spawn(
         GridContents::<_>::new((4, 4))
             .with_header("Bag of any! 4x4:")
             .builder()
+            .into_kind()
             .with_name("Paper doll".into())
+            .with_max_weight(20.0)
             .with_section_layout(Layout::new(Direction::TopDown, false))
             .with_sections(sections),
     );
@@ -411,7 +410,7 @@ fn spawn_items(
     commands.insert_resource(Ground(ground));
 }
 
-fn item_icon_changed<T: Accepts>(
+fn item_icon_changed(
     mut commands: Commands,
     mut icons: Query<(Entity, &Icon), Changed<Icon>>,
     mut textures: ResMut<EguiUserTextures>,
@@ -434,7 +433,6 @@ fn update(
     mut storage: ContentsStorage<Flags>,
     paper_doll: Res<PaperDoll>,
     ground: Res<Ground>,
-    opened: Query<(Entity, &Name), (With<Open>, Without<IsResource>)>,
 ) -> Result {
     let ctx = contexts.ctx_mut()?;
 
@@ -463,25 +461,8 @@ fn update(
             storage.show(ground.0, ui);
         });
 
-    // TODO Should containers opened in a window auto-raise, when dragged to? They can end up behind the fixed contents (ground, etc.).
-
     // Show all open containers.
-    for (c, name, ..) in &opened {
-        let mut open = true;
-        egui::Window::new(name.as_str())
-            .resizable(false)
-            .movable(true)
-            .open(&mut open)
-            // .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-16.0, 16.0))
-            .show(ctx, |ui| {
-                storage.show(c, ui);
-            });
-        if !open {
-            storage.commands.entity(c).remove::<Open>();
-            // .trigger(ContainerClose); // 14.2 doesn't have this yet?
-            storage.commands.trigger(ContainerClose(c));
-        }
-    }
+    storage.show_windows(ctx);
 
     Ok(())
 }
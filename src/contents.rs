@@ -1,32 +1,234 @@
 mod builder;
+mod custom;
 mod grid;
-
-use bevy_ecs::{entity::MapEntities, prelude::*, system::SystemParam};
+mod hex;
+mod highlight;
+mod list;
+mod occupancy;
+mod peek;
+mod spring;
+mod summary;
+mod validate;
+mod walk;
+
+use bevy_ecs::{
+    entity::{EntityHashMap, EntityMapper, MapEntities},
+    prelude::*,
+    system::SystemParam,
+};
 use bevy_egui::egui::{
     self,
     ecolor::{tint_color_towards, Color32},
+    style::WidgetVisuals,
     Align, Direction, Id, InnerResponse, Pos2, Rect, Response, Ui, Vec2,
 };
 use bevy_reflect::{Reflect, ReflectDeserialize, ReflectSerialize};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::*;
 pub use builder::*;
+pub use custom::*;
 pub use grid::*;
-
-// TODO: maybe this is doable https://github.com/bevyengine/bevy/blob/latest/examples/reflection/trait_reflection.rs
+pub use hex::*;
+pub use highlight::*;
+pub use list::*;
+pub use occupancy::*;
+pub use spring::*;
+pub use summary::*;
+pub use validate::*;
+pub use walk::*;
+
+// This can't be stored in `ContentsItems` since it can't be reflected (or saved). See `ContentsKind::Custom`.
 pub type BoxedContents<T> = Box<dyn Contents<T> + Send + Sync + 'static>;
 
-#[derive(Debug, Clone, MapEntities, Reflect, Eq, PartialEq)]
-pub struct SlotItem(usize, #[entities] Entity);
+/// An item and its slot, as passed to `Contents::body` and `Contents::ui`. See `ContentsStorage::slot_items`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SlotItem(pub(crate) usize, pub(crate) Entity);
+
+/// The container and slot an item is in. This is a relationship: the container's `Contains` lists its items and is kept up to date by Bevy, including when items are despawned. Scenes are loaded without relationship hooks, so use `Persist::load`, which rebuilds it. The slot is maintained by `ContentsStorage` (`spawn`, `insert`, drags and `repair`) along with the slots filled in the container's `ContentsItems`. Changes are made with commands, so they show up once commands are applied.
+#[derive(Component, Clone, Copy, Debug, Reflect, Eq, PartialEq)]
+#[reflect(Component, Debug)]
+#[relationship(relationship_target = Contains)]
+pub struct InContainer {
+    #[relationship]
+    #[entities]
+    pub container: Entity,
+    pub slot: usize,
+}
+
+/// Items in a container, in no particular order. This is the target of the `InContainer` relationship and is not saved since `Persist::load` rebuilds it from `InContainer`.
+#[derive(Component, Debug, Default)]
+#[relationship_target(relationship = InContainer)]
+pub struct Contains(Vec<Entity>);
 
-// In order to make this generic over a contents parameter (`C`), we'd also have to add the parameter to storage, which would then make the Contents trait self-referential (which makes it not object-safe). So we'd have to add a new Storage trait.
-#[derive(Component, Reflect)]
+/// The container a section belongs to. Set by `ContentsStorage::spawn`.
+#[derive(Component, Clone, Copy, Debug, MapEntities, Reflect, Eq, PartialEq)]
+#[reflect(Component, Debug)]
+pub struct SectionOf(#[entities] pub Entity);
+
+/// The contents of a container. Which items are in it is recorded by their `InContainer`, not here: the contents only track the slots the items fill. `ContentsStorage` keeps the two in step (`spawn`, `insert`, drags and `repair`). Code that calls `Contents::insert` or `Contents::remove` directly must also insert or remove `InContainer`, and since that's done with commands, the two disagree until commands are applied.
+#[derive(Component, Clone, Reflect)]
 #[reflect(Component)]
-pub struct ContentsItems<T> {
-    pub contents: GridContents<T>,
+pub struct ContentsItems<T: Accepts> {
     #[entities]
-    pub items: Vec<SlotItem>,
+    pub contents: ContentsKind<T>,
+}
+
+/// The kinds of contents. `ContentsItems` stores one of these so different kinds of containers can be mixed in one hierarchy (see `ContentsBuilder::into_kind`) and still be saved with scenes. User-defined contents go in `Custom` (see `ContentsKind::custom`).
+#[derive(Clone, Debug, Reflect)]
+pub enum ContentsKind<T: Accepts> {
+    Grid(GridContents<T>),
+    Hex(HexContents<T>),
+    List(ListContents<T>),
+    Custom(Custom<T>),
+}
+
+impl<T: Accepts> ContentsKind<T> {
+    /// Wraps user-defined contents. Register the type and its `ReflectContents` type data to save and load it with scenes.
+    pub fn custom(contents: impl CustomContents<T>) -> Self {
+        Self::Custom(Custom(Box::new(contents)))
+    }
+}
+
+impl<T: Accepts> MapEntities for ContentsKind<T> {
+    /// Custom contents aren't mapped, so they shouldn't store entities.
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        match self {
+            Self::Grid(c) => c.occupancy.map_entities(entity_mapper),
+            Self::Hex(c) => c.occupancy.map_entities(entity_mapper),
+            Self::List(_) | Self::Custom(_) => (),
+        }
+    }
+}
+
+impl<T: Accepts> From<GridContents<T>> for ContentsKind<T> {
+    fn from(contents: GridContents<T>) -> Self {
+        Self::Grid(contents)
+    }
+}
+
+impl<T: Accepts> From<HexContents<T>> for ContentsKind<T> {
+    fn from(contents: HexContents<T>) -> Self {
+        Self::Hex(contents)
+    }
+}
+
+impl<T: Accepts> From<ListContents<T>> for ContentsKind<T> {
+    fn from(contents: ListContents<T>) -> Self {
+        Self::List(contents)
+    }
+}
+
+// Forward to the contents of every kind.
+macro_rules! each_kind {
+    ($kind:expr, $c:ident => $e:expr) => {
+        match $kind {
+            ContentsKind::Grid($c) => $e,
+            ContentsKind::Hex($c) => $e,
+            ContentsKind::List($c) => $e,
+            ContentsKind::Custom($c) => $e,
+        }
+    };
+}
+
+impl<T: Accepts> Contents<T> for ContentsKind<T> {
+    fn slots(&self) -> usize {
+        each_kind!(self, c => c.slots())
+    }
+
+    fn used(&self) -> usize {
+        each_kind!(self, c => c.used())
+    }
+
+    fn packed(&self) -> bool {
+        each_kind!(self, c => c.packed())
+    }
+
+    fn insert(&mut self, slot: usize, id: Entity, item: &Item<T>) {
+        each_kind!(self, c => c.insert(slot, id, item))
+    }
+
+    fn remove(&mut self, slot: usize, id: Entity, item: &Item<T>) {
+        each_kind!(self, c => c.remove(slot, id, item))
+    }
+
+    fn occupant(&self, slot: usize) -> Option<Entity> {
+        each_kind!(self, c => c.occupant(slot))
+    }
+
+    fn blocking(&self, item: &Item<T>, slot: usize) -> Vec<Entity> {
+        each_kind!(self, c => c.blocking(item, slot))
+    }
+
+    fn clear(&mut self) {
+        each_kind!(self, c => c.clear())
+    }
+
+    fn pos(&self, slot: usize) -> Vec2 {
+        each_kind!(self, c => c.pos(slot))
+    }
+
+    fn slot(&self, offset: Vec2) -> usize {
+        each_kind!(self, c => c.slot(offset))
+    }
+
+    fn accepts(&self, item: &Item<T>) -> bool {
+        each_kind!(self, c => c.accepts(item))
+    }
+
+    fn fits(&self, id: Entity, item: &Item<T>, slot: usize, source: &DragSource) -> bool {
+        each_kind!(self, c => c.fits(id, item, slot, source))
+    }
+
+    fn find_slot(
+        &self,
+        id: Entity,
+        item: &Item<T>,
+        source: &DragSource,
+    ) -> Option<(Entity, usize)> {
+        each_kind!(self, c => c.find_slot(id, item, source))
+    }
+
+    fn rotate(&self, drag: &mut DragItem<T>) {
+        each_kind!(self, c => c.rotate(drag))
+    }
+
+    fn mirror(&self, drag: &mut DragItem<T>) {
+        each_kind!(self, c => c.mirror(drag))
+    }
+
+    fn preview(&self, id: Entity, drag: &DragItem<T>, slot: usize, ui: &mut Ui) {
+        each_kind!(self, c => c.preview(id, drag, slot, ui))
+    }
+
+    fn shadow_color(&self, accepts: bool, fits: bool, ui: &egui::Ui) -> egui::Color32 {
+        each_kind!(self, c => c.shadow_color(accepts, fits, ui))
+    }
+
+    fn body(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut egui::Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        each_kind!(self, c => c.body(id, contents, items, ui))
+    }
+
+    fn paint(&self, contents: &ContentsStorage<T>, items: &[SlotItem], ui: &mut egui::Ui) {
+        each_kind!(self, c => c.paint(contents, items, ui))
+    }
+
+    fn ui(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut egui::Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        each_kind!(self, c => c.ui(id, contents, items, ui))
+    }
 }
 
 /// egui::Layout is not serializable (egui::Direction is). Furthermore, some of the alignment values just don't work well (e.g. centering). So we just make our own struct with only direction and wrapping.
@@ -78,6 +280,10 @@ pub struct Sections(pub Option<Layout>, #[entities] pub Vec<Entity>);
 #[derive(Debug)]
 pub enum ContentsResponse<T> {
     NewTarget((Entity, usize, Id)),
+    /// A new target for the dragged item once it's rotated 90° this many times (see `Snap`). The rotation is applied on drop.
+    RotatedTarget((Entity, usize, Id), usize),
+    /// A new target inside the container item (the last field) the item is dragged onto (see `Options::spring_delay`).
+    ItemTarget((Entity, usize, Id), Entity),
     NewDrag(DragItem<T>),
     SendItem(DragItem<T>),
     Open(Entity),
@@ -97,6 +303,8 @@ pub struct DragItem<T> {
     pub source: DragSource,
     /// Target container id and slot, and the egui Id of the widget who set the target.
     pub target: Option<(Entity, usize, Id)>,
+    /// 90° rotations applied to the item when it's dropped on the target, if the target was found by snapping (see `Snap`).
+    pub target_rotations: usize,
     /// Relative offset inside the item where the drag started.
     pub offset: Vec2,
     /// Relative offset outside the item, close to the inner offset.
@@ -109,6 +317,9 @@ pub struct DragItem<T> {
 
 pub const OUTER_DISTANCE: f32 = 6.0;
 
+/// Slot size in pixels for the miniature contents shown when dragging onto a container item (see `Contents::preview`).
+pub const PREVIEW_SLOT_SIZE: f32 = 12.0;
+
 impl<T> DragItem<T> {
     pub fn new(id: Entity, item: Item<T>) -> Self {
         Self {
@@ -116,6 +327,7 @@ impl<T> DragItem<T> {
             item,
             source: None,
             target: None,
+            target_rotations: 0,
             offset: Vec2::ZERO,
             outer_offset: Vec2::ZERO,
             origin: Pos2::ZERO,
@@ -123,9 +335,15 @@ impl<T> DragItem<T> {
         }
     }
 
+    // Rotates by `rotation`, rebuilding the shape from the untransformed shape so it always matches the transform, even when switching between square and hex rotations.
+    fn rotate_to(&mut self, rotation: ItemRotation) {
+        let base = self.item.transform().unapply(&self.item.shape);
+        self.item.rotation = rotation;
+        self.item.shape = self.item.transform().apply(&base);
+    }
+
     fn rotate90(&mut self) {
-        self.item.rotation = self.item.rotation.increment();
-        self.item.shape = self.item.shape.rotate90();
+        self.rotate_to(self.item.rotation.increment());
 
         // This is close but not quite right. This also leaves the slot incorrect...
         if !self.item.shape.is_square() {
@@ -134,6 +352,29 @@ impl<T> DragItem<T> {
             self.outer_offset = self.outer_offset.yx();
         }
     }
+
+    fn rotate60(&mut self) {
+        self.rotate_to(self.item.rotation.increment_hex());
+    }
+
+    // Clears mirroring and keeps the rotation, for hex containers (see `ItemTransform`).
+    fn unmirror(&mut self) {
+        if self.item.mirror {
+            let base = self.item.transform().unapply(&self.item.shape);
+            self.item.mirror = false;
+            self.item.shape = self.item.transform().apply(&base);
+        }
+    }
+
+    // Mirrors the item left to right. `slot_dim` is used to mirror the offsets.
+    fn mirror(&mut self, slot_dim: f32) {
+        self.item.set_transform(self.item.transform().mirrored());
+        self.item.shape = self.item.shape.flip_x();
+
+        let width = self.item.shape.size.x as f32 * slot_dim;
+        self.offset.x = width - self.offset.x;
+        self.outer_offset.x = width - self.outer_offset.x;
+    }
 }
 
 /// Accepts must be cloned because items must be cloned.
@@ -160,6 +401,25 @@ pub struct Options {
     pub section_layout: Layout,
     /// Inline contents layout.
     pub inline_layout: Layout,
+    /// Unit shown after container weights.
+    pub weight_unit: &'static str,
+    /// Snapping for items dragged over grids. Off by default.
+    pub snap: Snap,
+    /// If set, dragging an item onto a container item and holding it there this many seconds opens the container (with `ContainerOpen`), so the item can be dropped in a particular slot. It's closed again (with `ContainerClose`) when the drag moves to a target outside it, stays off every target for the same delay, or ends anywhere but inside it. Containers whose contents are already shown aren't opened. Off by default.
+    pub spring_delay: Option<f32>,
+    /// If set, hovering a container item previews its contents (and sections) in the tooltip, at this scale, e.g. 0.5. Off by default.
+    pub hover_preview: Option<f32>,
+    /// What container headers show besides the header text and weight. Off by default.
+    pub summary: Summary,
+    /// If set, every shown container and section is tinted while dragging, depending on whether it has room for the dragged item, is full, or rejects it (see `DropTarget`). Off by default.
+    pub drop_highlight: Option<DropHighlight>,
+}
+
+/// When the slot under the pointer doesn't fit the dragged item, grids look for the nearest slot that does, up to `radius` slots away in each direction, and target it instead. If `rotate` is set, other rotations are tried too, and the dragged item is rotated to match when dropped. A radius of zero without rotation turns snapping off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snap {
+    pub radius: u32,
+    pub rotate: bool,
 }
 
 impl Default for Options {
@@ -169,13 +429,27 @@ impl Default for Options {
             layout: Layout::new(Direction::TopDown, false),
             section_layout: Default::default(),
             inline_layout: Default::default(),
+            weight_unit: "kg",
+            snap: Snap::default(),
+            spring_delay: None,
+            hover_preview: None,
+            summary: Summary::default(),
+            drop_highlight: None,
         }
     }
 }
 
+/// Filter for every entity that is part of an inventory: contents, sections and items.
+pub type InventoryFilter<T> = Or<(
+    With<ContentsItems<T>>,
+    With<Sections>,
+    With<InContainer>,
+    With<Contains>,
+)>;
+
 /// Contents storage.
 #[derive(SystemParam)]
-pub struct ContentsStorage<'w, 's, T: Send + Sync + 'static> {
+pub struct ContentsStorage<'w, 's, T: Accepts> {
     pub commands: Commands<'w, 's>,
     pub contents: Query<
         'w,
@@ -186,6 +460,13 @@ pub struct ContentsStorage<'w, 's, T: Send + Sync + 'static> {
     >,
     pub items: Query<'w, 's, (&'static Name, &'static mut Item<T>, Option<&'static IconId>)>,
     pub sections: Query<'w, 's, &'static Sections>,
+    pub weights: Query<'w, 's, &'static Weight>,
+    pub max_weights: Query<'w, 's, &'static MaxWeight>,
+    pub in_container: Query<'w, 's, &'static InContainer>,
+    pub members: Query<'w, 's, &'static Contains>,
+    pub nodes: Query<'w, 's, Entity, InventoryFilter<T>>,
+    pub section_of: Query<'w, 's, &'static SectionOf>,
+    pub open: Query<'w, 's, (Entity, &'static Open)>,
 
     // pub container_flags: Query<'w, 's, &'static ContainerFlags<T>>,
     // pub item_flags: Query<'w, 's, &'static ItemFlags<T>>,
@@ -197,6 +478,10 @@ pub struct ContentsStorage<'w, 's, T: Send + Sync + 'static> {
     pub target: Local<'s, Option<Entity>>,
 
     pub options: Res<'w, Options>,
+
+    spring: Local<'s, SpringLoad>,
+
+    drop_targets: Local<'s, DropTargets>,
 }
 
 impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
@@ -212,14 +497,32 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
                     })
                 }
 
+                let target = drag.target.map(|t| t.0);
                 self.resolve_drag(drag);
+                self.spring_end(target);
             }
         }
 
+        let left = self.drag.as_ref().is_some_and(|d| d.target.is_none());
+        self.spring_left(left, ctx);
+
         if let Some(drag) = self.drag.as_mut() {
-            // Rotate the dragged item.
-            if ctx.input(|i| i.key_pressed(egui::Key::R)) {
-                drag.rotate90();
+            // Rotate or mirror (flip) the dragged item. The target container (or the source if there is no target) determines how.
+            let [rotate, mirror] =
+                ctx.input(|i| [egui::Key::R, egui::Key::F].map(|k| i.key_pressed(k)));
+            if rotate || mirror {
+                let contents = drag
+                    .target
+                    .map(|t| t.0)
+                    .or(drag.source.as_ref().map(|s| s.0))
+                    .and_then(|id| self.contents.get(id).ok());
+
+                match contents {
+                    Some(c) if rotate => c.contents.rotate(drag),
+                    Some(c) => c.contents.mirror(drag),
+                    None if rotate => drag.rotate90(),
+                    None => (),
+                }
             }
         }
 
@@ -238,16 +541,22 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
 
     pub fn set_drag_target(&mut self, target: Option<(Entity, usize, Id)>) {
         if let Some(drag) = self.drag.as_mut() {
+            drag.target_rotations = 0;
+
             // set_if_neq?
             if drag.target != target {
                 drag.target = target;
 
                 if let Some((entity, slot, _)) = target {
-                    self.commands.trigger(ItemDragOver {
-                        entity,
-                        slot,
-                        item: drag.id,
-                    });
+                    if let Ok(ContentsKind::Hex(_)) =
+                        self.contents.get(entity).map(|ci| &ci.contents)
+                    {
+                        drag.unmirror();
+                    }
+
+                    let item = drag.id;
+                    self.commands.trigger(ItemDragOver { entity, slot, item });
+                    self.spring_close(Some(entity));
                 }
             }
         }
@@ -255,12 +564,25 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
 
     /// Show contents for container `id` and update the current drag.
     pub fn show(&mut self, id: Entity, ui: &mut Ui) -> Option<Response> {
+        self.spring_shown(id, ui.ctx());
+        self.update_drop_targets();
         let InnerResponse { inner, response } = self.show_contents(id, ui)?;
 
         match inner {
             Some(ContentsResponse::NewTarget((id, slot, _))) => {
                 // Overwrite the egui id. The original is effectively unused.
-                self.set_drag_target(Some((id, slot, ui.id())))
+                self.set_drag_target(Some((id, slot, ui.id())));
+                self.spring_hover(None, ui.ctx());
+            }
+            Some(ContentsResponse::ItemTarget((id, slot, _), item)) => {
+                self.set_drag_target(Some((id, slot, ui.id())));
+                self.spring_hover(Some(item), ui.ctx());
+            }
+            Some(ContentsResponse::RotatedTarget((id, slot, _), rotations)) => {
+                self.set_drag_target(Some((id, slot, ui.id())));
+                if let Some(drag) = self.drag.as_mut() {
+                    drag.target_rotations = rotations;
+                }
             }
             Some(ContentsResponse::NewDrag(new_drag)) => {
                 *self.drag = Some(new_drag);
@@ -281,7 +603,7 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
             }
             Some(ContentsResponse::SendItem(mut item)) => {
                 item.target = self.target.and_then(|t| {
-                    self.find_slot(t, &item.item, &item.source)
+                    self.find_slot(t, item.id, &item.item, &item.source)
                         .map(|(id, slot)| (id, slot, ui.id()))
                 });
                 self.resolve_drag(item);
@@ -296,6 +618,7 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
                 if response.contains_pointer() || self.target_eid().is_some_and(|id| id == ui.id())
                 {
                     self.set_drag_target(None);
+                    self.spring_hover(None, ui.ctx());
                 }
             }
         }
@@ -313,21 +636,38 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
         ui: &mut Ui,
     ) -> Option<InnerResponse<Option<ContentsResponse<T>>>> {
         let c = self.get(id).unwrap();
-        Some(c.contents.ui(id, self, &c.items, ui))
+        let items = self.members_of(id).collect_vec();
+        Some(c.contents.ui(id, self, &items, ui))
     }
 
     pub fn get(&self, id: Entity) -> Option<&ContentsItems<T>> {
         self.contents.get(id).ok()
     }
 
+    /// Items in container `id`, in no particular order. See `slot_items`.
+    pub fn members_of(&self, id: Entity) -> impl Iterator<Item = SlotItem> + '_ {
+        self.members.get(id).into_iter().flat_map(|c| {
+            c.iter()
+                .filter_map(|i| self.in_container.get(i).ok().map(|c| SlotItem(c.slot, i)))
+        })
+    }
+
+    /// Items in container `id`, ordered by slot.
+    pub fn slot_items(&self, id: Entity) -> Vec<SlotItem> {
+        let mut items = self.members_of(id).collect_vec();
+        items.sort_by_key(|SlotItem(slot, _)| *slot);
+        items
+    }
+
+    /// Fetches the name, item and icon of `items`. Entities that aren't items are skipped (see `validate`).
     // TODO: naming
     pub fn items<'a>(
         &'a self,
         items: &'a [SlotItem],
     ) -> impl Iterator<Item = (&'a SlotItem, (&'a Name, &'a Item<T>, Option<&'a IconId>))> {
-        let q_items = self.items.iter_many(items.iter().map(|i| i.1));
-        // This is absolutely an error if the entities don't exist.
-        itertools::zip_eq(items, q_items)
+        items
+            .iter()
+            .filter_map(|i| self.items.get(i.1).ok().map(|q| (i, q)))
     }
 
     /// Inserts item with `id` into `container`. Returns final container id and slot.
@@ -335,10 +675,149 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
         let item = self.items.get(id).ok()?.1;
 
         // This is fetching twice...
-        let (container, slot) = self.find_slot(container, item, &None)?;
+        let (container, slot) = self.find_slot(container, id, item, &None)?;
         let mut ci = self.contents.get_mut(container).ok()?;
 
-        ci.insert(slot, id, item);
+        ci.contents.insert(slot, id, item);
+        self.shift_slots(container, None, Some(slot), id);
+        self.commands
+            .entity(id)
+            .insert(InContainer { container, slot });
+        Some((container, slot))
+    }
+
+    // Packed contents keep their items in order from the first slot, so removing from and/or inserting into a slot shifts the slots of the items after it. Both are done at once since the new slots aren't visible until commands are applied.
+    fn shift_slots(
+        &mut self,
+        container: Entity,
+        removed: Option<usize>,
+        inserted: Option<usize>,
+        except: Entity,
+    ) {
+        if !self.get(container).is_some_and(|c| c.contents.packed()) {
+            return;
+        }
+
+        let Ok(members) = self.members.get(container) else {
+            return;
+        };
+
+        // Relationships are immutable, so reinsert with the new slot.
+        for i in members.iter().filter(|i| *i != except) {
+            if let Ok(&InContainer { slot, .. }) = self.in_container.get(i) {
+                let s = removed.map_or(slot, |r| if slot > r { slot - 1 } else { slot });
+                let s = inserted.map_or(s, |n| if s >= n { s + 1 } else { s });
+                // The item may be despawning along with its container.
+                if s != slot {
+                    self.commands
+                        .entity(i)
+                        .try_insert(InContainer { container, slot: s });
+                }
+            }
+        }
+    }
+
+    /// Removes item `id` from its container. The item is not despawned. Cleanup is done by `on_remove_item`.
+    pub fn remove(&mut self, id: Entity) {
+        self.commands.entity(id).remove::<InContainer>();
+    }
+
+    /// Despawns `id` along with its contents and sections, recursively. Only `id` fires `ItemRemove`, if it's in a container. Despawning a container by other means leaves its items (and sections) behind, outside of any container, without firing `ItemRemove`.
+    pub fn despawn(&mut self, id: Entity) {
+        for (i, _) in self.walk_all(id).collect_vec() {
+            self.commands.entity(i).insert(Detached).try_despawn();
+        }
+        self.commands.entity(id).try_despawn();
+    }
+
+    /// Spawns a copy of `id` along with its contents and sections, recursively, and returns the new root. Components are copied with Bevy's entity cloning, so other cloneable components come along too. Links between the copies are remapped, the new root is not in any container, and the copies aren't open (see `Open`).
+    pub fn deep_clone(&mut self, id: Entity) -> Entity {
+        let old = std::iter::once(id)
+            .chain(self.walk_all(id).map(|(i, _)| i))
+            .collect_vec();
+
+        let map: EntityHashMap<Entity> = old
+            .iter()
+            .map(|&i| {
+                let new = self
+                    .commands
+                    .entity(i)
+                    .clone_and_spawn_with_opt_out(|b| {
+                        b.deny::<(InContainer, Contains, Sections, SectionOf, Open)>();
+                    })
+                    .id();
+                (i, new)
+            })
+            .collect();
+
+        // The root keeps no links outside of the copy.
+        for i in old.into_iter().skip(1) {
+            let mut e = self.commands.entity(map[&i]);
+
+            if let Ok(c) = self.in_container.get(i) {
+                e.insert(InContainer {
+                    container: map[&c.container],
+                    slot: c.slot,
+                });
+            }
+
+            if let Ok(s) = self.section_of.get(i) {
+                e.insert(SectionOf(map[&s.0]));
+            }
+        }
+
+        // Generic components aren't cloned by `clone_and_spawn`, so copy them here. Occupancy refers to the items, so point it at the copies.
+        let mut mapper = map.clone();
+        for (i, new) in &map {
+            if let Ok(s) = self.sections.get(*i) {
+                let sections = Sections(s.0, s.1.iter().map(|s| map[s]).collect());
+                self.commands.entity(*new).insert(sections);
+            }
+
+            if let Ok((_, item, _)) = self.items.get(*i) {
+                self.commands.entity(*new).insert(item.clone());
+            }
+
+            if let Ok(ci) = self.contents.get(*i) {
+                let mut ci = ci.clone();
+                ci.contents.map_entities(&mut mapper);
+                self.commands.entity(*new).insert(ci);
+            }
+        }
+
+        map[&id]
+    }
+
+    // Unpaints item `id` from its container, shifts the slots after it (for packed contents) and fires `ItemRemove`.
+    fn unlink(&mut self, id: Entity) {
+        let Some((container, slot)) = self.unpaint(id) else {
+            return;
+        };
+
+        // The container was despawned, so there's nothing to remove the item from.
+        if !self.contents.contains(container) {
+            return;
+        }
+
+        self.commands.trigger(ItemRemove {
+            entity: container,
+            slot,
+            item: id,
+        });
+    }
+
+    // Like `unlink`, without firing `ItemRemove`. Returns the container and slot the item was in.
+    pub(crate) fn unpaint(&mut self, id: Entity) -> Option<(Entity, usize)> {
+        let &InContainer { container, slot } = self.in_container.get(id).ok()?;
+
+        // The container may already be gone.
+        if let (Ok(mut ci), Ok((_, item, _))) =
+            (self.contents.get_mut(container), self.items.get(id))
+        {
+            ci.contents.remove(slot, id, item);
+        }
+
+        self.shift_slots(container, Some(slot), None, id);
         Some((container, slot))
     }
 
@@ -349,20 +828,15 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
     /// Returns true if the contents of `a` contains `b`. Recursively checks both contained items and sections so `b` can be an item or contents.
     pub fn contains(&self, a: Entity, b: Entity) -> bool {
         // a == b ||
-        self.contents.get(a).is_ok_and(|c| {
-            c.items
-                .iter()
-                .any(|SlotItem(_, i)| *i == b || self.contains(*i, b))
-        }) || self
-            .sections
-            .get(a)
-            .is_ok_and(|s| s.1.iter().any(|s| *s == b || self.contains(*s, b)))
+        self.walk_all(a).any(|(id, _)| id == b)
     }
 
+    /// Finds a slot for item `item_id` in container `id` or its sections, skipping containers it would make too heavy (see `fits_weight`).
     // Check sections first or last? Last is less recursion.
     pub fn find_slot(
         &self,
         id: Entity,
+        item_id: Entity,
         item: &Item<T>,
         source: &DragSource,
     ) -> Option<(Entity, usize)> {
@@ -370,6 +844,7 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
             self.contents
                 .get(id)
                 .ok()
+                .filter(|_| self.fits_weight(id, item_id))
                 .and_then(|ci| ci.contents.find_slot(id, item, source))
         };
 
@@ -381,12 +856,19 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
         })
     }
 
-    pub fn resolve_drag(&mut self, drag: DragItem<T>) {
+    pub fn resolve_drag(&mut self, mut drag: DragItem<T>) {
+        // Apply the snapped rotation.
+        (0..drag.target_rotations).for_each(|_| drag.rotate90());
+
         let DragItem {
             id,
-            item: Item {
-                shape, rotation, ..
-            },
+            item:
+                Item {
+                    shape,
+                    rotation,
+                    mirror,
+                    ..
+                },
             source: Some((container_id, container_slot, _)),
             target: Some((target_id, slot, ..)),
             ..
@@ -396,7 +878,7 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
             return;
         };
 
-        if id == target_id || self.contains(id, target_id) {
+        if id == target_id || self.ancestors(target_id).any(|a| a == id) {
             return tracing::info!("cannot move an item inside itself");
         }
 
@@ -417,16 +899,30 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
         };
 
         // Remove from source container.
-        src.remove(container_slot, id, item.as_ref());
+        src.contents.remove(container_slot, id, item.as_ref());
 
-        // Copy rotation and shape from the dragged item. Do this before inserting so the shape is painted correctly.
-        if item.rotation != rotation {
+        // Copy the transform and shape from the dragged item. Do this before inserting so the shape is painted correctly.
+        // Compare shapes too, since hex rotations and unmirroring trim them.
+        if (&item.shape, item.rotation, item.mirror) != (&shape, rotation, mirror) {
             item.shape = shape;
             item.rotation = rotation;
+            item.mirror = mirror;
         }
 
-        // Insert into destination container (or source if same). TODO: put slot_item back on error?
-        dest.unwrap_or(src).insert(slot, id, item.as_ref());
+        // Insert into destination container (or source if same). TODO: put item back on error?
+        dest.unwrap_or(src).contents.insert(slot, id, item.as_ref());
+
+        if container_id == target_id {
+            self.shift_slots(container_id, Some(container_slot), Some(slot), id);
+        } else {
+            self.shift_slots(container_id, Some(container_slot), None, id);
+            self.shift_slots(target_id, None, Some(slot), id);
+        }
+
+        self.commands.entity(id).insert(InContainer {
+            container: target_id,
+            slot,
+        });
 
         // Fire events.
         if container_id == target_id {
@@ -452,35 +948,26 @@ impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
     }
 }
 
+/// Cleans up after an item leaves its container without a drag, e.g. when it is despawned. Added by `RunicPlugin`.
+pub fn on_remove_item<T: Accepts>(
+    remove: On<Remove, InContainer>,
+    detached: Query<(), With<Detached>>,
+    mut storage: ContentsStorage<T>,
+) {
+    // Items detached by `ContentsStorage::repair` were never painted, a failed `Persist::load` despawns entities nobody has seen, and `ContentsStorage::despawn` takes their containers too.
+    if !detached.contains(remove.entity) {
+        storage.unlink(remove.entity);
+    }
+}
+
 impl<T> ContentsItems<T>
 where
     T: Accepts,
 {
-    // TODO: more checking and return a Result? same for removal
-    pub fn insert(&mut self, slot: usize, id: Entity, item: &Item<T>) {
-        assert!(slot < self.contents.slots(), "slot in contents length");
-
-        // Multiple items can share the same slot if they fit together.
-        let i = self
-            .items
-            .binary_search_by(|SlotItem(k, _)| k.cmp(&slot))
-            // .expect_err("item slot free");
-            .unwrap_or_else(|i| i);
-        self.items.insert(i, SlotItem(slot, id));
-
-        self.contents.insert(slot, item);
-    }
-
-    // return something must_use? no dangling items...
-    pub fn remove(&mut self, slot: usize, id: Entity, item: &Item<T>) {
-        self.items
-            .iter()
-            .position(|slot_item| *slot_item == SlotItem(slot, id))
-            //.position(|(_, item)| item == id)
-            .map(|i| self.items.remove(i))
-            .expect("item exists");
-
-        self.contents.remove(slot, item);
+    pub fn new(contents: impl Into<ContentsKind<T>>) -> Self {
+        Self {
+            contents: contents.into(),
+        }
     }
 }
 
@@ -496,9 +983,36 @@ pub trait Contents<T: Accepts> {
     /// Number of slots this container holds.
     fn slots(&self) -> usize;
 
-    fn insert(&mut self, slot: usize, item: &Item<T>);
+    /// Number of slots filled by items, out of `slots`. The default counts the slots with a known occupant (see `occupant`).
+    fn used(&self) -> usize {
+        (0..self.slots())
+            .filter(|&slot| self.occupant(slot).is_some())
+            .count()
+    }
+
+    /// If true, items are kept in order and packed from the first slot (like a list), so inserting or removing an item shifts the slots of the items after it.
+    fn packed(&self) -> bool {
+        false
+    }
+
+    /// Marks the slots filled by `item` (with `id`) at `slot`. This doesn't add the item to the container; see `ContentsItems`.
+    fn insert(&mut self, slot: usize, id: Entity, item: &Item<T>);
+
+    /// Unmarks the slots filled by `item` (with `id`) at `slot`. This doesn't remove the item from the container; see `ContentsItems`.
+    fn remove(&mut self, slot: usize, id: Entity, item: &Item<T>);
 
-    fn remove(&mut self, slot: usize, item: &Item<T>);
+    /// The item filling `slot`, if known. Only grid and hex contents keep track (see `Occupancy`).
+    fn occupant(&self, _slot: usize) -> Option<Entity> {
+        None
+    }
+
+    /// The items in the way of `item` at `slot`, e.g. to explain why it doesn't fit or to swap with. Only grid and hex contents keep track; parts of the item out of bounds are ignored.
+    fn blocking(&self, _item: &Item<T>, _slot: usize) -> Vec<Entity> {
+        Vec::new()
+    }
+
+    /// Removes all items, e.g. to rebuild occupancy from scratch (see `ContentsStorage::repair`).
+    fn clear(&mut self);
 
     /// Returns a position for a given slot relative to the contents' origin.
     fn pos(&self, slot: usize) -> Vec2;
@@ -516,6 +1030,19 @@ pub trait Contents<T: Accepts> {
     fn find_slot(&self, id: Entity, item: &Item<T>, source: &DragSource)
         -> Option<(Entity, usize)>;
 
+    /// Rotates the dragged item one step. The default is 90° for square grids.
+    fn rotate(&self, drag: &mut DragItem<T>) {
+        drag.rotate90();
+    }
+
+    /// Mirrors the dragged item. The default does nothing since only grids support it.
+    fn mirror(&self, _drag: &mut DragItem<T>) {}
+
+    /// Shows a miniature of the contents (`id`) with the dragged item at `slot`, for dropping onto a container item. The default just names the slot.
+    fn preview(&self, _id: Entity, _drag: &DragItem<T>, slot: usize, ui: &mut Ui) {
+        ui.label(format!("Slot {}", slot + 1));
+    }
+
     fn shadow_color(&self, accepts: bool, fits: bool, ui: &egui::Ui) -> egui::Color32 {
         let color = if !accepts {
             Color32::GRAY
@@ -536,6 +1063,11 @@ pub trait Contents<T: Accepts> {
         ui: &mut egui::Ui,
     ) -> InnerResponse<Option<ContentsResponse<T>>>;
 
+    /// Paints the contents like `body`, without any interaction, e.g. for previews in tooltips (see `ContentsStorage::peek`). The default just counts the items.
+    fn paint(&self, _contents: &ContentsStorage<T>, items: &[SlotItem], ui: &mut egui::Ui) {
+        ui.label(format!("{} items", items.len()));
+    }
+
     /// Draw container.
     fn ui(
         &self,
@@ -546,6 +1078,122 @@ pub trait Contents<T: Accepts> {
     ) -> InnerResponse<Option<ContentsResponse<T>>>;
 }
 
+/// Draws the sections and header of container `id` around its contents frame (`add_contents`), followed by inline contents, if any.
+pub(crate) fn header_frame<T: Accepts>(
+    id: Entity,
+    header: Option<&str>,
+    inline: bool,
+    contents: &ContentsStorage<T>,
+    items: &[SlotItem],
+    ui: &mut Ui,
+    add_contents: impl FnOnce(&mut WidgetVisuals, &mut Ui) -> InnerResponse<Option<ContentsResponse<T>>>,
+) -> InnerResponse<Option<ContentsResponse<T>>> {
+    ui.with_layout(contents.options.layout.to_egui_layout(), |ui| {
+        // Sections. TODO: The entity mapping issue indicates there's a bad case here that needs to be caught? Like if the `id` isn't a container at all or doesn't exist?
+        let section_ir = contents.sections.get(id).ok().and_then(|s| {
+            ui.with_layout(
+                s.0.unwrap_or(contents.options.section_layout)
+                    .to_egui_layout(),
+                |ui| {
+                    // TODO faster to fetch many first?
+                    s.1.iter()
+                        .filter_map(|id| contents.show_contents(*id, ui))
+                        .filter_map(|ir| ir.inner)
+                        .at_most_one()
+                        .unwrap_or_else(|mut e| {
+                            tracing::error!("at most one item response");
+                            e.next()
+                        })
+                },
+            )
+            .inner
+        });
+
+        // TODO? The header should always be above the contents that it describes (i.e. use Ui::vertical here)?
+        let header = header
+            .map(str::to_owned)
+            .into_iter()
+            .chain(contents.summary_text(id))
+            .chain(contents.weight_text(id))
+            .join(" ");
+        contents.header_ui(id, &header, ui);
+
+        let ir = ui
+            .with_layout(contents.options.inline_layout.to_egui_layout(), |ui| {
+                // Go back to with_bg/min_frame since egui::Frame takes up all available space.
+                let ir: Option<ContentsResponse<T>> =
+                    crate::min_frame::min_frame(ui, |style, ui| {
+                        contents.drop_tint(id, style);
+                        add_contents(style, ui)
+                    })
+                    .inner;
+
+                ir.or(
+                    // Show inline contents.
+                    inline
+                        .then(|| {
+                            let drag_id = contents.drag.as_ref().map(|d| d.id);
+                            items
+                                .iter()
+                                .map(|SlotItem(_, id)| *id)
+                                // Don't add contents if the container is being dragged.
+                                .filter(|id| drag_id != Some(*id))
+                                .filter_map(|id| contents.show_contents(id, ui))
+                                .filter_map(|ir| ir.inner)
+                                .at_most_one()
+                                .unwrap_or_else(|mut e| {
+                                    tracing::error!("at most one item response");
+                                    e.next()
+                                })
+                        })
+                        .flatten(),
+                )
+            })
+            .inner;
+
+        section_ir.or(ir)
+    })
+}
+
+// Shows where the dragged item goes when dropped on container item `id`, next to the pointer: the containers from `id` down to `target` (which may be a section or nested deeper), and a miniature of the target with the item in place.
+pub(crate) fn drop_preview<T: Accepts>(
+    id: Entity,
+    target: Option<(Entity, usize)>,
+    contents: &ContentsStorage<T>,
+    drag: &DragItem<T>,
+    ui: &Ui,
+) {
+    egui::Tooltip::always_open(
+        ui.ctx().clone(),
+        ui.layer_id(),
+        ui.id().with("drop_preview"),
+        egui::PopupAnchor::Pointer,
+    )
+    .show(|ui| {
+        let Some((container, slot)) = target else {
+            ui.colored_label(ui.visuals().warn_fg_color, "No room");
+            return;
+        };
+
+        let mut path = vec![container];
+        if container != id {
+            path.extend(contents.ancestors(container).take_while(|a| *a != id));
+            path.push(id);
+        }
+        let names = path
+            .iter()
+            .rev()
+            .filter_map(|e| contents.items.get(*e).ok())
+            .map(|(name, ..)| name.as_str())
+            .join(" › ");
+        ui.label(names);
+
+        if let Some(ci) = contents.get(container) {
+            ci.contents.preview(container, drag, slot, ui);
+        }
+    });
+}
+
 pub fn xy(slot: usize, width: usize) -> Vec2 {
     Vec2::new((slot % width) as f32, (slot / width) as f32)
 }
@@ -599,3 +1247,182 @@ pub fn shape_mesh(
         });
     mesh
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn items_skips_non_items() {
+        let mut world = world();
+
+        let bag = ContentsItems::new(GridContents::<Flags>::new((2, 1)));
+        let bag = world.spawn(bag).id();
+        let in_bag = |slot| InContainer {
+            container: bag,
+            slot,
+        };
+        let junk = world.spawn(in_bag(0)).id();
+        let stone = world
+            .spawn((Name::new(""), Item::new(Flags::A), in_bag(1)))
+            .id();
+
+        let mut state = state(&mut world);
+        let storage = state.get_mut(&mut world).unwrap();
+        let slot_items = storage.slot_items(bag);
+        assert_eq!(slot_items, [SlotItem(0, junk), SlotItem(1, stone)]);
+        let items = storage.items(&slot_items).map(|(i, _)| *i).collect_vec();
+        assert_eq!(items, [SlotItem(1, stone)]);
+    }
+
+    #[test]
+    fn mixed_rotation() {
+        let shape = crate::shape!["##", "#."];
+        let item = Item::new(Flags::A).with_shape(shape.clone());
+        let mut drag = DragItem::new(Entity::PLACEHOLDER, item);
+
+        // Rotating in a hex container and then a grid (and back) keeps the shape in step with the transform.
+        drag.rotate60();
+        drag.rotate90();
+        assert_eq!(drag.item.rotation, ItemRotation::R90);
+        assert_eq!(drag.item.shape, shape.rotate90());
+        drag.rotate60();
+        assert_eq!(drag.item.rotation, ItemRotation::R60);
+        assert_eq!(drag.item.shape, shape.rotate60());
+
+        // Hex containers clear mirroring and keep the rotation.
+        drag.rotate90();
+        drag.mirror(1.0);
+        assert_eq!(drag.item.shape, shape.rotate90().flip_x());
+        drag.unmirror();
+        assert!(!drag.item.mirror);
+        assert_eq!(drag.item.shape, drag.item.rotation.apply(&shape));
+    }
+
+    #[test]
+    fn despawn() {
+        let mut world = world();
+        world.add_observer(on_remove_item::<Flags>);
+        track_removed(&mut world);
+
+        let grid = |w| ContentsItems::new(GridContents::<Flags>::new((w, 1)).with_flags(Flags::A));
+        let list = ContentsItems::new(ListContents::<Flags>::new(3).with_flags(Flags::A));
+        let item = Item::new(Flags::A);
+        let [bag, pouch] =
+            [grid(2), grid(1)].map(|g| world.spawn((g, Name::new(""), item.clone())).id());
+        let section = world.spawn(grid(1)).id();
+        world.entity_mut(bag).insert(Sections(None, vec![section]));
+        let list = world.spawn(list).id();
+        let [stone, a, b, c] = [(); 4].map(|_| world.spawn((Name::new(""), item.clone())).id());
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, pouch);
+        storage.insert(pouch, stone);
+        for i in [a, b, c] {
+            storage.insert(list, i);
+        }
+        state.apply(&mut world);
+
+        // Despawning an item unpaints it, shifts the items after it and fires `ItemRemove`.
+        world.despawn(a);
+        world.flush();
+        assert_eq!(world.resource::<Removed>().0, [(list, 0, a)]);
+        let storage = state.get_mut(&mut world).unwrap();
+        assert_eq!(storage.slot_items(list), [SlotItem(0, b), SlotItem(1, c)]);
+        let ci = storage.get(list).unwrap();
+        assert_eq!(ci.contents.find_slot(list, &item, &None), Some((list, 2)));
+
+        world.despawn(stone);
+        world.flush();
+        let storage = state.get_mut(&mut world).unwrap();
+        assert!(storage.slot_items(pouch).is_empty());
+        let ci = storage.get(pouch).unwrap();
+        assert!(ci.contents.fits(pouch, &item, 0, &None));
+
+        // Despawn the bag along with its contents and sections.
+        let stone = world.spawn((Name::new(""), item.clone())).id();
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(pouch, stone);
+        state.apply(&mut world);
+        world.resource_mut::<Removed>().0.clear();
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.despawn(bag);
+        state.apply(&mut world);
+        for e in [bag, pouch, stone, section] {
+            assert!(world.get_entity(e).is_err());
+        }
+
+        // Despawning containers doesn't fire `ItemRemove` for their contents, whether they go too or are left behind.
+        assert_eq!(world.resource::<Removed>().0, []);
+        world.despawn(list);
+        world.flush();
+        assert_eq!(world.resource::<Removed>().0, []);
+        assert!(world.get::<InContainer>(b).is_none());
+    }
+
+    #[test]
+    fn deep_clone() {
+        let mut world = world();
+
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((2, 2)).with_flags(Flags::A));
+        let item = || (Name::new(""), Item::new(Flags::A));
+        let section = world.spawn(grid()).id();
+        let bag = world.spawn((grid(), Sections(None, vec![section]))).id();
+        world.entity_mut(section).insert(SectionOf(bag));
+        let pouch = world
+            .spawn((grid(), item(), Weight(1.0), Open::default()))
+            .id();
+        let [stone, potion] = [(); 2].map(|_| world.spawn(item()).id());
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, pouch);
+        storage.insert(pouch, stone);
+        storage.insert(section, potion);
+        state.apply(&mut world);
+
+        let mut storage = state.get_mut(&mut world).unwrap();
+        let copy = storage.deep_clone(bag);
+        let pouch_copy = storage.deep_clone(pouch);
+        state.apply(&mut world);
+
+        let storage = state.get_mut(&mut world).unwrap();
+        let shape = |root| {
+            storage
+                .walk(root)
+                .map(|w| (w.slot, w.depth()))
+                .collect_vec()
+        };
+        assert_eq!(shape(copy), shape(bag));
+
+        // Nothing in the copy refers to the original.
+        let originals = [bag, section, pouch, stone, potion];
+        for w in storage.walk(copy) {
+            assert!(!originals.contains(&w.id));
+            assert!(!w.path.iter().any(|p| originals.contains(p)));
+        }
+        let sections = storage.sections.get(copy).unwrap();
+        assert!(!originals.contains(&sections.1[0]));
+        assert_eq!(storage.parent(sections.1[0]), Some(copy));
+
+        // Other components are copied too, but the copies aren't open. The copy of the pouch isn't in the bag.
+        assert_eq!(storage.total_weight(pouch_copy), 1.0);
+        assert!(!storage.open.contains(pouch_copy));
+        assert_eq!(storage.parent(pouch_copy), None);
+        assert_eq!(storage.walk(pouch_copy).count(), 1);
+        assert_eq!(storage.slot_items(bag).len(), 1);
+
+        // The copies are items and containers themselves, and their occupancy refers to the copied items.
+        assert!(storage.items.contains(pouch_copy));
+        assert!(storage.get(copy).is_some());
+        let contents = &storage.get(pouch_copy).unwrap().contents;
+        let [SlotItem(_, stone_copy)] = storage.slot_items(pouch_copy)[..] else {
+            panic!("one item in the copy of the pouch");
+        };
+        assert_ne!(stone_copy, stone);
+        assert_eq!(contents.occupant(0), Some(stone_copy));
+        assert_eq!(storage.validate(), []);
+    }
+}
@@ -7,6 +7,8 @@ pub struct ContentsBuilder<C, T> {
     name: Option<Name>,
     item: Option<Item<T>>,
     icon: Option<Icon>,
+    weight: Option<Weight>,
+    max_weight: Option<MaxWeight>,
     contents: Option<C>,
     section_layout: Option<Layout>,
     sections: Vec<ContentsBuilder<C, T>>,
@@ -19,6 +21,8 @@ impl<C, T> Default for ContentsBuilder<C, T> {
             name: None,
             item: None,
             icon: None,
+            weight: None,
+            max_weight: None,
             contents: None,
             section_layout: None,
             sections: Vec::new(),
@@ -27,28 +31,61 @@ impl<C, T> Default for ContentsBuilder<C, T> {
     }
 }
 
-// Too bad we can't do contents generically.
-// impl<T> From<BoxedContents<T>> for ContentsBuilder<T> {
-//     fn from(contents: BoxedContents<T>) -> Self {
-//         Self {
-//             contents: Some(contents),
-//             ..Default::default()
-//         }
-//     }
-// }
-
 impl<T> From<GridContents<T>> for ContentsBuilder<GridContents<T>, T>
 where
     T: Accepts + Copy + std::fmt::Debug,
 {
     fn from(contents: GridContents<T>) -> Self {
         Self {
-            contents: Some(contents), //.boxed(),
+            contents: Some(contents),
             ..Default::default()
         }
     }
 }
 
+impl<T> From<HexContents<T>> for ContentsBuilder<HexContents<T>, T>
+where
+    T: Accepts + Copy + std::fmt::Debug,
+{
+    fn from(contents: HexContents<T>) -> Self {
+        Self {
+            contents: Some(contents),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T> From<ListContents<T>> for ContentsBuilder<ListContents<T>, T>
+where
+    T: Accepts + Copy + std::fmt::Debug,
+{
+    fn from(contents: ListContents<T>) -> Self {
+        Self {
+            contents: Some(contents),
+            ..Default::default()
+        }
+    }
+}
+
+// Builders for mixed hierarchies use `ContentsKind`, so any of the built-in kinds can be a section. Custom contents are added with `ContentsBuilder::contents(ContentsKind::custom(..))`.
+macro_rules! kind_from {
+    ($($c:ident),*) => {
+        $(impl<T> From<$c<T>> for ContentsBuilder<ContentsKind<T>, T>
+        where
+            T: Accepts + Copy + std::fmt::Debug,
+        {
+            fn from(contents: $c<T>) -> Self {
+                Self {
+                    contents: Some(contents.into()),
+                    ..Default::default()
+                }
+            }
+        })*
+    };
+}
+
+kind_from!(GridContents, HexContents, ListContents);
+
 pub trait ContentsExt<C, T> {
     fn builder(self) -> ContentsBuilder<C, T>;
 }
@@ -85,7 +122,7 @@ impl<C, T> ContentsBuilder<C, T> {
         self
     }
 
-    pub fn contents(contents: C) -> Self
+    pub fn contents(contents: impl Into<C>) -> Self
     where
         T: Accepts,
         C: Contents<T> + Send + Sync + 'static,
@@ -93,12 +130,12 @@ impl<C, T> ContentsBuilder<C, T> {
         Self::default().with_contents(contents)
     }
 
-    pub fn with_contents(mut self, contents: C) -> Self
+    pub fn with_contents(mut self, contents: impl Into<C>) -> Self
     where
         T: Accepts,
         C: Contents<T> + Send + Sync + 'static,
     {
-        self.contents = Some(contents); // .boxed();
+        self.contents = Some(contents.into());
         self
     }
 
@@ -124,14 +161,59 @@ impl<C, T> ContentsBuilder<C, T> {
         self.icon = Some(Icon(icon));
         self
     }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = Some(Weight(weight));
+        self
+    }
+
+    pub fn with_max_weight(mut self, max_weight: f32) -> Self {
+        self.max_weight = Some(MaxWeight(max_weight));
+        self
+    }
+
+    // Weight of the item (if any) and everything in it.
+    fn total_weight(&self) -> f32 {
+        self.weight.map_or(0.0, |w| w.0)
+            + self
+                .sections
+                .iter()
+                .chain(&self.items)
+                .map(Self::total_weight)
+                .sum::<f32>()
+    }
+
+    /// Converts this builder (and all its sections and items) into one that can be mixed with other kinds of contents.
+    pub fn into_kind(self) -> ContentsBuilder<ContentsKind<T>, T>
+    where
+        T: Accepts,
+        C: Into<ContentsKind<T>>,
+    {
+        ContentsBuilder {
+            name: self.name,
+            item: self.item,
+            icon: self.icon,
+            weight: self.weight,
+            max_weight: self.max_weight,
+            contents: self.contents.map(Into::into),
+            section_layout: self.section_layout,
+            sections: self.sections.into_iter().map(Self::into_kind).collect(),
+            items: self.items.into_iter().map(Self::into_kind).collect(),
+        }
+    }
 }
 
 impl<T: Accepts + Clone> ContentsStorage<'_, '_, T> {
-    pub fn spawn(&mut self, contents: impl Into<ContentsBuilder<GridContents<T>, T>>) -> Entity {
+    pub fn spawn<C>(&mut self, contents: impl Into<ContentsBuilder<C, T>>) -> Entity
+    where
+        C: Into<ContentsKind<T>>,
+    {
         let ContentsBuilder {
             name,
             item,
             icon,
+            weight,
+            max_weight,
             contents,
             section_layout,
             sections,
@@ -145,6 +227,7 @@ impl<T: Accepts + Clone> ContentsStorage<'_, '_, T> {
             "sections => contents"
         );
 
+        let sections_weight: f32 = sections.iter().map(ContentsBuilder::total_weight).sum();
         let sections = Sections(
             section_layout,
             sections.into_iter().map(|i| self.spawn(i)).collect(),
@@ -152,11 +235,16 @@ impl<T: Accepts + Clone> ContentsStorage<'_, '_, T> {
 
         assert!(items.is_empty() || contents.is_some(), "items => contents");
 
+        // Sections count towards the max weight, and nested containers check their own.
+        if let Some(max) = max_weight {
+            let weight: f32 = items.iter().map(ContentsBuilder::total_weight).sum();
+            let weight = weight + sections_weight;
+            assert!(weight <= max.0, "contents exceed max weight");
+        }
+
+        let mut slot_items = Vec::new();
         let contents_items = contents.map(|contents| {
-            let mut contents_items = ContentsItems {
-                contents,
-                items: Vec::new(),
-            };
+            let mut contents_items = ContentsItems::new(contents);
 
             // This is convoluted because we can't fetch anything. None of these items exist yet.
             for item in items {
@@ -176,19 +264,32 @@ impl<T: Accepts + Clone> ContentsStorage<'_, '_, T> {
                 // The item might fit in a sub-container, but we don't have access to place it there. It would otherwise be viable. Fix?
                 assert_eq!(id, _id, "item fits in current container");
 
-                contents_items.insert(slot, id, &item_clone);
+                contents_items.contents.insert(slot, id, &item_clone);
+                slot_items.push(SlotItem(slot, id));
             }
 
             contents_items
         });
 
         let mut e = self.commands.spawn_empty();
+        let id = e.id();
+
+        // Link sections back to this container.
+        let section_ids = sections.1.clone();
 
         // Insert contents (and items).
         if let Some(contents_items) = contents_items {
             e.insert(contents_items);
         }
 
+        if let Some(weight) = weight {
+            e.insert(weight);
+        }
+
+        if let Some(max_weight) = max_weight {
+            e.insert(max_weight);
+        }
+
         // Insert sections.
         if !sections.1.is_empty() {
             e.insert(sections);
@@ -206,6 +307,17 @@ impl<T: Accepts + Clone> ContentsStorage<'_, '_, T> {
             e.insert(name);
         }
 
-        e.id()
+        for SlotItem(slot, item) in slot_items {
+            self.commands.entity(item).insert(InContainer {
+                container: id,
+                slot,
+            });
+        }
+
+        for section in section_ids {
+            self.commands.entity(section).insert(SectionOf(id));
+        }
+
+        id
     }
 }
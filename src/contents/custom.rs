@@ -0,0 +1,135 @@
+use bevy_reflect::{
+    serde::{
+        DeserializeWithRegistry, ReflectDeserializeWithRegistry, ReflectDeserializer,
+        ReflectSerializeWithRegistry, ReflectSerializer, SerializeWithRegistry,
+    },
+    FromType, ReflectFromReflect, TypeRegistry,
+};
+use serde::{de::Error, Deserializer, Serializer};
+
+use super::*;
+
+/// Contents other than the built-in kinds, stored in `ContentsKind::Custom`. Implemented for any `Contents` that is also `Reflect` and `Clone`. To load custom contents with scenes, register the type with `ReflectContents` type data.
+pub trait CustomContents<T: Accepts>: Contents<T> + Reflect {
+    fn clone_custom(&self) -> Box<dyn CustomContents<T>>;
+}
+
+impl<T: Accepts, C> CustomContents<T> for C
+where
+    C: Contents<T> + Reflect + Clone,
+{
+    fn clone_custom(&self) -> Box<dyn CustomContents<T>> {
+        Box::new(self.clone())
+    }
+}
+
+type FromReflectFn<T> =
+    fn(Box<dyn Reflect>) -> Result<Box<dyn CustomContents<T>>, Box<dyn Reflect>>;
+
+/// Type data for custom contents, used to turn loaded (reflected) contents back into `CustomContents`. Register it with `App::register_type_data::<C, ReflectContents<T>>`.
+pub struct ReflectContents<T> {
+    from_reflect: FromReflectFn<T>,
+}
+
+impl<T> Clone for ReflectContents<T> {
+    fn clone(&self) -> Self {
+        Self {
+            from_reflect: self.from_reflect,
+        }
+    }
+}
+
+impl<T: Accepts, C: CustomContents<T>> FromType<C> for ReflectContents<T> {
+    fn from_type() -> Self {
+        Self {
+            from_reflect: |value| {
+                value
+                    .downcast::<C>()
+                    .map(|c| c as Box<dyn CustomContents<T>>)
+            },
+        }
+    }
+}
+
+impl<T: Accepts> ReflectContents<T> {
+    pub fn get_boxed(
+        &self,
+        value: Box<dyn Reflect>,
+    ) -> Result<Box<dyn CustomContents<T>>, Box<dyn Reflect>> {
+        (self.from_reflect)(value)
+    }
+}
+
+/// User-defined contents (see `CustomContents`). Saved with scenes as the reflected contents along with their type path.
+#[derive(Reflect)]
+#[reflect(opaque, Clone, SerializeWithRegistry, DeserializeWithRegistry)]
+pub struct Custom<T: Accepts>(pub Box<dyn CustomContents<T>>);
+
+impl<T: Accepts> Clone for Custom<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_custom())
+    }
+}
+
+impl<T: Accepts> std::fmt::Debug for Custom<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Custom")
+            .field(&self.0.reflect_short_type_path())
+            .finish()
+    }
+}
+
+impl<T: Accepts> std::ops::Deref for Custom<T> {
+    type Target = dyn CustomContents<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl<T: Accepts> std::ops::DerefMut for Custom<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.0
+    }
+}
+
+impl<T: Accepts> SerializeWithRegistry for Custom<T> {
+    fn serialize<S>(&self, serializer: S, registry: &TypeRegistry) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde::Serialize::serialize(
+            &ReflectSerializer::new(self.0.as_partial_reflect(), registry),
+            serializer,
+        )
+    }
+}
+
+impl<'de, T: Accepts> DeserializeWithRegistry<'de> for Custom<T> {
+    fn deserialize<D>(deserializer: D, registry: &TypeRegistry) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde::de::DeserializeSeed::deserialize(
+            ReflectDeserializer::new(registry),
+            deserializer,
+        )?;
+        let type_path = value
+            .get_represented_type_info()
+            .map(|info| info.type_path())
+            .ok_or_else(|| D::Error::custom("custom contents without type info"))?;
+        let registration = registry
+            .get_with_type_path(type_path)
+            .ok_or_else(|| D::Error::custom(format!("unregistered custom contents {type_path}")))?;
+        let value = registration
+            .data::<ReflectFromReflect>()
+            .and_then(|r| r.from_reflect(&*value))
+            .ok_or_else(|| D::Error::custom(format!("no FromReflect for {type_path}")))?;
+        let contents = registration
+            .data::<ReflectContents<T>>()
+            .ok_or_else(|| D::Error::custom(format!("no ReflectContents for {type_path}")))?
+            .get_boxed(value)
+            .map_err(|_| D::Error::custom(format!("{type_path} is not contents")))?;
+        Ok(Self(contents))
+    }
+}
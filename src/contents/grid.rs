@@ -1,32 +1,75 @@
-use bevy_egui::egui::{self, style::WidgetVisuals, Rect, StrokeKind, Ui};
+use bevy_egui::egui::{self, Rect, StrokeKind, Ui};
+use bevy_math::I64Vec2;
 use itertools::Itertools;
 
 use super::*;
 
 /// Contains items in a 2d grid.
 #[derive(Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "GridData<T>"))]
 pub struct GridContents<T, const N: usize = 64> {
     /// If true, this grid only holds one item, but the size of that item can be any up to the maximum size.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub expands: bool,
     /// If true, show inline contents for the contained item.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub inline: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub header: Option<String>, // Use Name?
     /// The shape describes the dimensions of the container and which slots are filled.
     pub shape: Shape,
+    /// Which item fills each slot of the shape. Not serialized: it's empty after deserializing, so rebuild it with `ContentsStorage::repair`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub occupancy: Occupancy,
     /// Flags determine what kinds of items will be accepted (see `Accepts`).
     pub flags: T,
 }
 
+// The serialized fields of `GridContents`. The occupancy is sized to the shape when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GridData<T> {
+    #[serde(default)]
+    expands: bool,
+    #[serde(default)]
+    inline: bool,
+    #[serde(default)]
+    header: Option<String>,
+    shape: Shape,
+    flags: T,
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> From<GridData<T>> for GridContents<T, N> {
+    fn from(data: GridData<T>) -> Self {
+        Self {
+            expands: data.expands,
+            inline: data.inline,
+            header: data.header,
+            occupancy: Occupancy::new(data.shape.area()),
+            shape: data.shape,
+            flags: data.flags,
+        }
+    }
+}
+
 impl<T, const N: usize> GridContents<T, N>
 where
     T: Accepts,
 {
     pub fn new(size: impl Into<Size>) -> Self {
+        let size = size.into();
         Self {
             expands: false,
             inline: false,
             header: None,
-            shape: Shape::new(size.into(), false),
+            shape: Shape::new(size, false),
+            occupancy: Occupancy::new(size.element_product() as usize),
             flags: T::default(),
         }
     }
@@ -51,6 +94,68 @@ where
         self
     }
 
+    /// Returns the container slots covered by `shape` placed at `slot`. Parts of the shape outside the container are skipped.
+    pub fn cells<'a>(&'a self, shape: &'a Shape, slot: usize) -> impl Iterator<Item = usize> + 'a {
+        let origin = self.shape.pos(slot);
+        shape.slots().filter_map(move |s| {
+            let pos = origin + shape.pos(s);
+            pos.cmplt(self.shape.size)
+                .all()
+                .then(|| self.shape.slot(pos))
+        })
+    }
+
+    // Check if the shape fits here. When moving within one container, use the cached shape with the dragged item (and original rotation) unpainted.
+    fn fits_shape(&self, id: Entity, shape: &Shape, slot: usize, source: &DragSource) -> bool {
+        let contents = match source {
+            Some((source_id, _, shape)) if id == *source_id => shape,
+            _ => &self.shape,
+        };
+
+        contents.fits(shape, slot)
+    }
+
+    /// Finds where the dragged item fits, nearest to `slot` (see `Snap`). Returns the slot, the number of 90° rotations, and the rotated shape. Fewer rotations are preferred over nearer slots.
+    pub fn snap(
+        &self,
+        id: Entity,
+        drag: &DragItem<T>,
+        slot: usize,
+        snap: Snap,
+    ) -> Option<(usize, usize, Shape)> {
+        let radius = if self.expands { 0 } else { snap.radius as i64 };
+        let rotations = if snap.rotate { 4 } else { 1 };
+        let origin = self.shape.pos(slot).as_i64vec2();
+        let Size { x: w, y: h } = self.shape.size;
+
+        let mut shape = drag.item.shape.clone();
+        let mut tried = Vec::new();
+        for r in 0..rotations {
+            if r > 0 {
+                shape = shape.rotate90();
+            }
+            // Symmetric shapes repeat.
+            if tried.contains(&shape) {
+                continue;
+            }
+
+            let nearest = (-radius..=radius)
+                .flat_map(|dy| (-radius..=radius).map(move |dx| I64Vec2::new(dx, dy)))
+                .map(|d| (origin + d, d.length_squared()))
+                .filter(|(p, _)| (0..w as i64).contains(&p.x) && (0..h as i64).contains(&p.y))
+                .map(|(p, d)| (self.shape.slot(p.as_uvec2()), d))
+                .filter(|(s, _)| self.fits_shape(id, &shape, *s, &drag.source))
+                .min_by_key(|&(s, d)| (d, s));
+
+            if let Some((s, _)) = nearest {
+                return Some((s, r, shape));
+            }
+            tried.push(shape.clone());
+        }
+
+        None
+    }
+
     /// Single slot dimensions in pixels.
     pub const fn slot_size() -> egui::Vec2 {
         egui::Vec2::splat(N as f32)
@@ -78,7 +183,7 @@ where
             let x = x as f32 * N as f32;
             egui::Shape::LineSegment {
                 points: [egui::Pos2::new(x, 0.0), egui::Pos2::new(x, h)],
-                stroke: stroke2.clone(),
+                stroke: stroke2,
             }
         }));
 
@@ -86,7 +191,7 @@ where
             let y = y as f32 * N as f32;
             egui::Shape::LineSegment {
                 points: [egui::Pos2::new(0.0, y), egui::Pos2::new(w, y)],
-                stroke: stroke2.clone(),
+                stroke: stroke2,
             }
         }));
 
@@ -113,12 +218,85 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
         }
     }
 
-    fn insert(&mut self, slot: usize, item: &Item<T>) {
+    fn used(&self) -> usize {
+        let used = self.shape.slots().count();
+        if self.expands {
+            used.min(1)
+        } else {
+            used
+        }
+    }
+
+    fn insert(&mut self, slot: usize, id: Entity, item: &Item<T>) {
         self.shape.paint(&item.shape, slot);
+        let cells = self.cells(&item.shape, slot).collect_vec();
+        self.occupancy.fill(cells, id);
     }
 
-    fn remove(&mut self, slot: usize, item: &Item<T>) {
+    fn remove(&mut self, slot: usize, id: Entity, item: &Item<T>) {
         self.shape.unpaint(&item.shape, slot);
+        let cells = self.cells(&item.shape, slot).collect_vec();
+        self.occupancy.clear(cells, id);
+    }
+
+    fn clear(&mut self) {
+        self.shape = Shape::new(self.shape.size, false);
+        self.occupancy = Occupancy::new(self.shape.area());
+    }
+
+    fn occupant(&self, slot: usize) -> Option<Entity> {
+        self.occupancy.get(slot)
+    }
+
+    fn blocking(&self, item: &Item<T>, slot: usize) -> Vec<Entity> {
+        self.occupancy.blocking(self.cells(&item.shape, slot))
+    }
+
+    fn mirror(&self, drag: &mut DragItem<T>) {
+        drag.mirror(N as f32);
+    }
+
+    fn preview(&self, id: Entity, drag: &DragItem<T>, slot: usize, ui: &mut Ui) {
+        let scale = PREVIEW_SLOT_SIZE / N as f32;
+        let size = if self.expands {
+            drag.item.shape.size
+        } else {
+            self.shape.size
+        };
+        let (rect, _) = ui.allocate_exact_size(self.grid_size(size) * scale, egui::Sense::hover());
+
+        // Leave the dragged item out if it's from here.
+        let shape = match &drag.source {
+            Some((source_id, _, shape)) if id == *source_id => shape,
+            _ => &self.shape,
+        };
+
+        let visuals = ui.visuals();
+        let filled = visuals.widgets.inactive.bg_fill;
+        let color = self.shadow_color(true, true, ui);
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+        painter.add(shape_mesh(
+            shape,
+            rect,
+            egui::Vec2::ZERO,
+            filled,
+            PREVIEW_SLOT_SIZE,
+        ));
+        let offset = self.pos(slot) * scale;
+        painter.add(shape_mesh(
+            &drag.item.shape,
+            rect,
+            offset,
+            color,
+            PREVIEW_SLOT_SIZE,
+        ));
+        painter.rect_stroke(
+            rect,
+            0.0,
+            visuals.widgets.noninteractive.bg_stroke,
+            StrokeKind::Outside,
+        );
     }
 
     fn pos(&self, slot: usize) -> egui::Vec2 {
@@ -144,15 +322,7 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
     }
 
     fn fits(&self, id: Entity, item: &Item<T>, slot: usize, source: &DragSource) -> bool {
-        // Check if the shape fits here. When moving within
-        // one container, use the cached shape with the
-        // dragged item (and original rotation) unpainted.
-        let shape = match source {
-            Some((source_id, _, shape)) if id == *source_id => shape,
-            _ => &self.shape,
-        };
-
-        shape.fits(&item.shape, slot)
+        self.fits_shape(id, &item.shape, slot, source)
     }
 
     fn find_slot(
@@ -171,6 +341,33 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
             .map(|slot| (id, slot))
     }
 
+    fn paint(&self, contents: &ContentsStorage<T>, items: &[SlotItem], ui: &mut Ui) {
+        let mut items = contents.items(items).peekable();
+        let grid_size = if self.expands {
+            items
+                .peek()
+                .map(|(_, (_, item, _))| item.shape.size)
+                .unwrap_or(Size::ONE)
+        } else {
+            self.shape.size
+        };
+
+        let (rect, _) = ui.allocate_exact_size(self.grid_size(grid_size), egui::Sense::hover());
+        if ui.is_rect_visible(rect) {
+            let mut grid = self.grid_shape(ui.style(), grid_size);
+            grid.translate(rect.min.to_vec2());
+            ui.painter().add(grid);
+
+            for (&SlotItem(slot, item_id), (_, item, icon)) in items {
+                let item_rect = Rect::from_min_size(rect.min + self.pos(slot), Self::slot_size());
+                let icon = icon.map(|icon| icon.0).unwrap_or_default();
+                ui.scope_builder(egui::UiBuilder::new().max_rect(item_rect), |ui| {
+                    item.body(item_id, 0.0, icon, N as f32, ui)
+                });
+            }
+        }
+    }
+
     fn body(
         &self,
         id: Entity,
@@ -220,7 +417,7 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
                             slot,
                             item_id,
                             name,
-                            contents.drag.as_ref(),
+                            contents,
                             icon.map(|icon| icon.0).unwrap_or_default(),
                             N as f32,
                             ui,
@@ -304,67 +501,8 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
         //     _ => (), // we could be dragging something else
         // }
 
-        let header_frame = |ui: &mut Ui, add_contents| {
-            ui.with_layout(contents.options.layout.to_egui_layout(), |ui| {
-                // Sections. TODO: The entity mapping issue indicates there's a bad case here that needs to be caught? Like if the `id` isn't a container at all or doesn't exist?
-                let section_ir = contents.sections.get(id).ok().and_then(|s| {
-                    ui.with_layout(
-                        s.0.unwrap_or(contents.options.section_layout)
-                            .to_egui_layout(),
-                        |ui| {
-                            // TODO faster to fetch many first?
-                            s.1.iter()
-                                .filter_map(|id| contents.show_contents(*id, ui))
-                                .filter_map(|ir| ir.inner)
-                                .at_most_one()
-                                .unwrap_or_else(|mut e| {
-                                    tracing::error!("at most one item response");
-                                    e.next()
-                                })
-                        },
-                    )
-                    .inner
-                });
-
-                // TODO? The header should always be above the contents that it describes (i.e. use Ui::vertical here)?
-                if let Some(header) = self.header.as_ref() {
-                    _ = ui.label(header)
-                }
-
-                let ir = ui
-                    .with_layout(contents.options.inline_layout.to_egui_layout(), |ui| {
-                        // Go back to with_bg/min_frame since egui::Frame takes up all available space.
-                        let ir: Option<ContentsResponse<T>> =
-                            crate::min_frame::min_frame(ui, add_contents).inner;
-
-                        ir.or(
-                            // Show inline contents.
-                            self.inline
-                                .then(|| {
-                                    let drag_id = contents.drag.as_ref().map(|d| d.id);
-                                    items
-                                        .iter()
-                                        .map(|SlotItem(_, id)| *id)
-                                        // Don't add contents if the container is being dragged.
-                                        .filter(|id| drag_id != Some(*id))
-                                        .filter_map(|id| contents.show_contents(id, ui))
-                                        .filter_map(|ir| ir.inner)
-                                        .at_most_one()
-                                        .unwrap_or_else(|mut e| {
-                                            tracing::error!("at most one item response");
-                                            e.next()
-                                        })
-                                })
-                                .flatten(),
-                        )
-                    })
-                    .inner;
-
-                section_ir.or(ir)
-            })
-        };
-
-        header_frame(ui, |style: &mut WidgetVisuals, ui: &mut Ui| {
+        let header = self.header.as_deref();
+        header_frame(id, header, self.inline, contents, items, ui, |style, ui| {
             // Reserve shape for the dragged item's shadow.
             let shadow = ui.painter().add(egui::Shape::Noop);
 
@@ -377,16 +515,16 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
                     if contents.is_container(id) {
                         // Rather than cloning the item every frame on hover, we just refetch it. This probably could be eliminated by clarifying some lifetimes and just passing an item ref back.
                         let item = contents.items.get(id).expect("item exists").1;
-                        let target = contents.find_slot(id, &drag.item, &drag.source);
+                        let target = contents.find_slot(id, drag.id, &drag.item, &drag.source);
 
                         // The item shadow is the target item for drag-to-item, not the dragged item.
                         let color = self.shadow_color(true, target.is_some(), ui);
                         let mesh =
                             shape_mesh(&item.shape, min_rect, self.pos(slot), color, N as f32);
                         ui.painter().set(shadow, mesh);
+                        drop_preview(id, target, contents, drag, ui);
 
-                        target
-                            .map(|(item, slot)| ContentsResponse::NewTarget((item, slot, ui.id())))
+                        target.map(|(c, slot)| ContentsResponse::ItemTarget((c, slot, ui.id()), id))
                     } else {
                         // Don't set target to non-contents.
                         None
@@ -423,14 +561,19 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
                             self.slot(p - min_rect.min - drag.offset + Self::slot_size() * 0.5)
                         });
 
-                    let fits = slot
-                        .map(|slot| self.fits(id, &drag.item, slot, &drag.source))
-                        .unwrap_or_default();
+                    // Snap to the nearest fit, which is the slot itself if it fits.
+                    let snapped = slot
+                        .filter(|_| accepts && contents.fits_weight(id, drag.id))
+                        .and_then(|slot| self.snap(id, drag, slot, contents.options.snap));
 
                     // Paint the dragged item's shadow, showing which slots will be filled.
-                    if let Some(slot) = slot {
+                    let mesh = match (&snapped, slot) {
+                        (Some((slot, _, shape)), _) => Some((*slot, shape, true)),
+                        (None, Some(slot)) => Some((slot, &drag.item.shape, false)),
+                        _ => None,
+                    };
+                    if let Some((slot, shape, fits)) = mesh {
                         let color = self.shadow_color(accepts, fits, ui);
-                        let shape = &drag.item.shape;
                         let mesh = shape_mesh(shape, min_rect, self.pos(slot), color, N as f32);
                         ui.painter().set(shadow, mesh);
                     }
@@ -445,8 +588,10 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
                     //     );
                     // }
 
-                    slot.filter(|_| accepts && fits)
-                        .map(|slot| ContentsResponse::NewTarget((id, slot, ui.id())))
+                    snapped.map(|(slot, rotations, _)| match rotations {
+                        0 => ContentsResponse::NewTarget((id, slot, ui.id())),
+                        r => ContentsResponse::RotatedTarget((id, slot, ui.id()), r),
+                    })
                 }
 
                 (_, inner) => inner,
@@ -456,3 +601,76 @@ impl<T: Accepts, const N: usize> Contents<T> for GridContents<T, N> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn snap() {
+        let stone = Item::new(Flags::A).with_shape(Shape::new((2, 1), true));
+        let mut grid = GridContents::<Flags>::new((3, 2)).with_flags(Flags::A);
+        grid.insert(0, Entity::PLACEHOLDER, &stone);
+        let id = Entity::PLACEHOLDER;
+        let drag = |shape| DragItem::new(id, Item::new(Flags::A).with_shape(shape));
+        let slot = |snapped: Option<(usize, usize, Shape)>| snapped.map(|(s, r, _)| (s, r));
+
+        // Off by default. The nearest fit is below.
+        let wide = drag(Shape::new((2, 1), true));
+        let off = Snap::default();
+        assert_eq!(slot(grid.snap(id, &wide, 0, off)), None);
+        let near = Snap {
+            radius: 1,
+            rotate: false,
+        };
+        assert_eq!(slot(grid.snap(id, &wide, 0, near)), Some((3, 0)));
+        assert_eq!(slot(grid.snap(id, &wide, 1, near)), Some((4, 0)));
+
+        // Only fits on its side.
+        let tall = drag(Shape::new((1, 3), true));
+        assert_eq!(slot(grid.snap(id, &tall, 0, near)), None);
+        let rotate = Snap {
+            radius: 1,
+            rotate: true,
+        };
+        assert_eq!(slot(grid.snap(id, &tall, 0, rotate)), Some((3, 1)));
+    }
+
+    #[test]
+    fn snap_on_drop() {
+        let mut world = world();
+
+        let grid = |size| ContentsItems::new(GridContents::<Flags>::new(size).with_flags(Flags::A));
+        let ground = world.spawn(grid((3, 1))).id();
+        let bag = world.spawn(grid((1, 3))).id();
+        let stick = world
+            .spawn((
+                Name::new(""),
+                Item::new(Flags::A).with_shape(Shape::new((3, 1), true)),
+            ))
+            .id();
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(ground, stick);
+        state.apply(&mut world);
+
+        // The snapped rotation is only applied on drop.
+        let mut storage = state.get_mut(&mut world).unwrap();
+        let item = storage.items.get(stick).unwrap().1.clone();
+        let mut drag = DragItem::new(stick, item);
+        drag.source = Some((ground, 0, Shape::new((3, 1), false)));
+        drag.target = Some((bag, 0, Id::NULL));
+        drag.target_rotations = 1;
+        storage.resolve_drag(drag);
+        state.apply(&mut world);
+
+        let storage = state.get_mut(&mut world).unwrap();
+        let item = storage.items.get(stick).unwrap().1;
+        assert_eq!(item.rotation, ItemRotation::R90);
+        assert_eq!(item.shape.size, Size::new(1, 3));
+        assert_eq!(storage.slot_items(bag), [SlotItem(0, stick)]);
+        assert_eq!(storage.validate(), []);
+    }
+}
@@ -0,0 +1,669 @@
+use bevy_egui::egui::{self, CursorIcon, Modifiers, Rect, Rgba, Sense, TextureId, Ui};
+use bevy_math::UVec2;
+
+use super::*;
+
+pub(crate) const SQRT_3: f32 = 1.732_050_8;
+
+/// Contains items in a grid of pointy-top hexagons. Odd rows are shifted right by half a cell so the grid stays roughly rectangular.
+///
+/// Item shapes are read as axial coordinates: each row of an item's shape is shifted half a cell right of the row above. Items rotate in 60° steps (see `Shape::rotate60`).
+#[derive(Clone, Debug, Reflect)]
+pub struct HexContents<T, const N: usize = 64> {
+    pub header: Option<String>,
+    /// The shape describes the dimensions of the container (in offset rows) and which cells are filled.
+    pub shape: Shape,
+    /// Which item fills each cell of the shape.
+    pub occupancy: Occupancy,
+    /// Flags determine what kinds of items will be accepted (see `Accepts`).
+    pub flags: T,
+}
+
+impl<T, const N: usize> HexContents<T, N>
+where
+    T: Accepts,
+{
+    pub fn new(size: impl Into<Size>) -> Self {
+        let size = size.into();
+        Self {
+            header: None,
+            shape: Shape::new(size, false),
+            occupancy: Occupancy::new(size.element_product() as usize),
+            flags: T::default(),
+        }
+    }
+
+    pub fn with_flags(mut self, flags: impl Into<T>) -> Self {
+        self.flags = flags.into();
+        self
+    }
+
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Single cell dimensions in pixels. `N` is the width of a cell (flat side to flat side).
+    pub fn cell_size() -> egui::Vec2 {
+        egui::vec2(N as f32, N as f32 * 2.0 / SQRT_3)
+    }
+
+    /// Grid dimensions in pixels.
+    pub fn grid_size(&self) -> egui::Vec2 {
+        let Size { x: w, y: h } = self.shape.size;
+        let shift = if h > 1 { 0.5 } else { 0.0 };
+        egui::vec2(
+            (w as f32 + shift) * N as f32,
+            axial_to_pixel(0.0, h.saturating_sub(1) as f32, N as f32).y + Self::cell_size().y,
+        )
+    }
+
+    /// Returns the center of the cell at `slot` relative to the contents' origin.
+    pub fn center(&self, slot: usize) -> egui::Vec2 {
+        let UVec2 { x, y } = self.shape.pos(slot);
+        let (q, r) = to_axial(x as i32, y as i32);
+        axial_to_pixel(q as f32, r as f32, N as f32) + Self::cell_size() * 0.5
+    }
+
+    /// Returns the cell containing the point (relative to the contents' origin), if any.
+    pub fn cell(&self, p: egui::Vec2) -> Option<usize> {
+        let (col, row) = to_offset(pixel_to_axial(p - Self::cell_size() * 0.5, N as f32));
+        self.offset_cell(col, row)
+    }
+
+    /// Returns the container cells covered by `shape` placed at `slot`, or `None` if any part of the shape falls outside the container.
+    pub fn cells(&self, shape: &Shape, slot: usize) -> Option<Vec<usize>> {
+        if slot >= self.shape.area() {
+            return None;
+        }
+
+        self.shape_cells(shape, slot).collect()
+    }
+
+    // Each cell of `shape` placed at `slot`, or `None` for those outside the container.
+    fn shape_cells<'a>(
+        &'a self,
+        shape: &'a Shape,
+        slot: usize,
+    ) -> impl Iterator<Item = Option<usize>> + 'a {
+        let UVec2 { x, y } = self.shape.pos(slot);
+        let (q, r) = to_axial(x as i32, y as i32);
+
+        shape.slots().map(move |s| {
+            let UVec2 { x: dq, y: dr } = shape.pos(s);
+            let (col, row) = to_offset((q + dq as i32, r + dr as i32));
+            self.offset_cell(col, row)
+        })
+    }
+
+    fn offset_cell(&self, col: i32, row: i32) -> Option<usize> {
+        let Size { x: w, y: h } = self.shape.size;
+        ((0..w as i32).contains(&col) && (0..h as i32).contains(&row))
+            .then(|| self.shape.slot(UVec2::new(col as u32, row as u32)))
+    }
+
+    /// Grid lines shape.
+    pub fn grid_shape(&self, style: &egui::Style) -> egui::Shape {
+        let mut stroke = style.visuals.widgets.noninteractive.bg_stroke;
+        stroke.color = tint_color_towards(stroke.color, style.visuals.extreme_bg_color);
+
+        egui::Shape::Vec(
+            (0..self.shape.area())
+                .map(|slot| {
+                    let center = self.center(slot).to_pos2();
+                    egui::Shape::closed_line(hexagon(center, N as f32).to_vec(), stroke)
+                })
+                .collect(),
+        )
+    }
+
+    fn paint_cells(&mut self, item: &Item<T>, slot: usize, id: Entity, fill: bool) {
+        match self.cells(&item.shape, slot) {
+            Some(cells) => {
+                cells.iter().for_each(|&c| self.shape.fill[c] = fill);
+                if fill {
+                    self.occupancy.fill(cells, id);
+                } else {
+                    self.occupancy.clear(cells, id);
+                }
+            }
+            None => tracing::error!("item shape outside hex contents at slot {slot}"),
+        }
+    }
+}
+
+impl<T: Accepts, const N: usize> Contents<T> for HexContents<T, N> {
+    fn slots(&self) -> usize {
+        self.shape.area()
+    }
+
+    fn used(&self) -> usize {
+        self.shape.slots().count()
+    }
+
+    fn insert(&mut self, slot: usize, id: Entity, item: &Item<T>) {
+        self.paint_cells(item, slot, id, true);
+    }
+
+    fn remove(&mut self, slot: usize, id: Entity, item: &Item<T>) {
+        self.paint_cells(item, slot, id, false);
+    }
+
+    fn clear(&mut self) {
+        self.shape = Shape::new(self.shape.size, false);
+        self.occupancy = Occupancy::new(self.shape.area());
+    }
+
+    fn occupant(&self, slot: usize) -> Option<Entity> {
+        self.occupancy.get(slot)
+    }
+
+    fn blocking(&self, item: &Item<T>, slot: usize) -> Vec<Entity> {
+        if slot >= self.shape.area() {
+            return Vec::new();
+        }
+
+        self.occupancy
+            .blocking(self.shape_cells(&item.shape, slot).flatten())
+    }
+
+    fn pos(&self, slot: usize) -> egui::Vec2 {
+        self.center(slot) - Self::cell_size() * 0.5
+    }
+
+    fn slot(&self, p: egui::Vec2) -> usize {
+        let (col, row) = to_offset(pixel_to_axial(p - Self::cell_size() * 0.5, N as f32));
+        let Size { x: w, y: h } = self.shape.size;
+        self.shape.slot(UVec2::new(
+            col.clamp(0, w as i32 - 1) as u32,
+            row.clamp(0, h as i32 - 1) as u32,
+        ))
+    }
+
+    fn accepts(&self, item: &Item<T>) -> bool {
+        self.flags.accepts(&item.flags)
+    }
+
+    fn fits(&self, id: Entity, item: &Item<T>, slot: usize, source: &DragSource) -> bool {
+        // When moving within one container, use the cached shape with the dragged item unpainted.
+        let shape = match source {
+            Some((source_id, _, shape)) if id == *source_id => shape,
+            _ => &self.shape,
+        };
+
+        self.cells(&item.shape, slot)
+            .is_some_and(|cells| cells.into_iter().all(|c| !shape.fill[c]))
+    }
+
+    fn find_slot(
+        &self,
+        id: Entity,
+        item: &Item<T>,
+        source: &DragSource,
+    ) -> Option<(Entity, usize)> {
+        if !self.accepts(item) {
+            return None;
+        }
+
+        (0..self.slots())
+            .find(|slot| self.fits(id, item, *slot, source))
+            .map(|slot| (id, slot))
+    }
+
+    fn rotate(&self, drag: &mut DragItem<T>) {
+        drag.rotate60();
+    }
+
+    fn preview(&self, id: Entity, drag: &DragItem<T>, slot: usize, ui: &mut Ui) {
+        let scale = PREVIEW_SLOT_SIZE / N as f32;
+        let (rect, _) = ui.allocate_exact_size(self.grid_size() * scale, Sense::hover());
+
+        // Leave the dragged item out if it's from here.
+        let shape = match &drag.source {
+            Some((source_id, _, shape)) if id == *source_id => shape,
+            _ => &self.shape,
+        };
+        let cells = self.cells(&drag.item.shape, slot).unwrap_or_default();
+
+        let visuals = ui.visuals();
+        let color = self.shadow_color(true, true, ui);
+        let mut mesh = egui::Mesh::default();
+        for c in 0..self.shape.area() {
+            let fill = if cells.contains(&c) {
+                color
+            } else if shape.fill[c] {
+                visuals.widgets.inactive.bg_fill
+            } else {
+                visuals.extreme_bg_color
+            };
+            let center = rect.min + self.center(c) * scale;
+            add_hexagon(&mut mesh, center, fill, PREVIEW_SLOT_SIZE);
+        }
+        ui.painter().add(mesh);
+    }
+
+    fn paint(&self, contents: &ContentsStorage<T>, items: &[SlotItem], ui: &mut Ui) {
+        let (rect, _) = ui.allocate_exact_size(self.grid_size(), Sense::hover());
+        if ui.is_rect_visible(rect) {
+            let mut grid = self.grid_shape(ui.style());
+            grid.translate(rect.min.to_vec2());
+            ui.painter().add(grid);
+
+            for (&SlotItem(slot, _), (_, item, icon)) in contents.items(items) {
+                let center = rect.min + self.pos(slot) + Self::cell_size() * 0.5;
+                let icon = icon.map(|icon| icon.0).unwrap_or_default();
+                paint_icon(item, center, icon, 0.0, N as f32, ui);
+            }
+        }
+    }
+
+    fn body(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        assert!(items.len() <= self.slots());
+
+        let n = N as f32;
+        let (rect, response) = ui.allocate_exact_size(self.grid_size(), Sense::hover());
+
+        let new_drag = if ui.is_rect_visible(rect) {
+            let grid_shape = ui.painter().add(egui::Shape::Noop);
+
+            let p = ui.ctx().pointer_latest_pos();
+
+            // Unlike the square grid, items can't be hit-tested by their rect since neighboring items overlap. So find the cell under the pointer and check which item covers it.
+            let hover_cell = p
+                .filter(|_| response.contains_pointer())
+                .and_then(|p| self.cell(p - rect.min));
+
+            let new_drag = contents
+                .items(items)
+                .filter_map(|(&SlotItem(slot, item_id), (name, item, icon))| {
+                    let eid = Id::new(item_id);
+                    let icon = icon.map(|icon| icon.0).unwrap_or_default();
+                    let origin = rect.min + self.pos(slot);
+
+                    let drag_id = contents.drag.as_ref().filter(|d| d.id == item_id);
+                    let drag_scale = ui.ctx().animate_bool(eid.with("scale"), drag_id.is_some());
+
+                    match drag_id {
+                        // This item is being dragged. Draw it in a new area (see `Item::ui`).
+                        Some(drag) => {
+                            ui.output_mut(|o| o.cursor_icon = CursorIcon::Grab);
+
+                            if let Some(p) = p {
+                                egui::containers::Area::new(eid)
+                                    .fixed_pos(drag.origin.lerp(p - drag.outer_offset, drag_scale))
+                                    .interactable(false)
+                                    .show(ui.ctx(), |ui| {
+                                        let size = item_size(&drag.item.shape, n);
+                                        let (r, _) = ui.allocate_exact_size(size, Sense::hover());
+                                        let center = r.min + Self::cell_size() * 0.5;
+                                        paint_icon(&drag.item, center, icon, drag_scale, n, ui);
+                                    });
+                            }
+
+                            None
+                        }
+                        _ => {
+                            let center = origin + Self::cell_size() * 0.5;
+                            paint_icon(item, center, icon, drag_scale, n, ui);
+
+                            let cell = hover_cell.filter(|cell| {
+                                self.cells(&item.shape, slot)
+                                    .is_some_and(|cells| cells.contains(cell))
+                            })?;
+
+                            // Dragging a different item? Drag to item.
+                            if contents.drag.is_some() {
+                                return Some(ContentsResponse::NewTarget((item_id, slot, ui.id())));
+                            }
+
+                            ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                            let cell_rect = Rect::from_center_size(
+                                rect.min + self.center(cell),
+                                Self::cell_size(),
+                            );
+                            let response = ui.interact(cell_rect, eid, Sense::click_and_drag());
+                            let response = contents.hover_ui(item_id, item, name, response);
+
+                            if response.double_clicked() {
+                                Some(ContentsResponse::Open(item_id))
+                            } else if response.clicked()
+                                && ui.input(|i| i.modifiers.contains(Modifiers::CTRL))
+                            {
+                                Some(ContentsResponse::SendItem(DragItem::new(
+                                    item_id,
+                                    item.clone(),
+                                )))
+                            } else if response.drag_started() {
+                                let offset = p? - origin;
+                                Some(ContentsResponse::NewDrag(DragItem {
+                                    offset,
+                                    outer_offset: outer_offset(
+                                        offset,
+                                        item_size(&item.shape, n),
+                                        OUTER_DISTANCE,
+                                    ),
+                                    origin,
+                                    offset_slot: cell,
+                                    ..DragItem::new(item_id, item.clone())
+                                }))
+                            } else {
+                                None
+                            }
+                        }
+                    }
+                    .map(|mut cr| {
+                        match cr {
+                            // Set source. Contents id, current slot and container shape w/ the item unpainted.
+                            ContentsResponse::NewDrag(ref mut drag)
+                            | ContentsResponse::SendItem(ref mut drag) => {
+                                let mut cshape = self.shape.clone();
+                                self.cells(&drag.item.shape, slot)
+                                    .into_iter()
+                                    .flatten()
+                                    .for_each(|c| cshape.fill[c] = false);
+                                drag.source = Some((id, slot, cshape));
+                            }
+                            _ => (),
+                        }
+                        cr
+                    })
+                })
+                .at_most_one()
+                .unwrap_or_else(|mut e| {
+                    tracing::warn!("more than one item response");
+                    e.next()
+                });
+
+            let mut grid = self.grid_shape(ui.style());
+            grid.translate(rect.min.to_vec2());
+            ui.painter().set(grid_shape, grid);
+
+            // debug paint the container "shape" (filled cells)
+            if ui.ctx().debug_on_hover() {
+                let shape = contents
+                    .drag
+                    .as_ref()
+                    .and_then(|d| d.source.as_ref())
+                    .filter(|s| id == s.0)
+                    .map(|d| &d.2)
+                    .unwrap_or(&self.shape);
+
+                let mut mesh = egui::Mesh::default();
+                shape.slots().for_each(|slot| {
+                    let color = Color32::GREEN.gamma_multiply(0.8);
+                    add_hexagon(&mut mesh, rect.min + self.center(slot), color, n)
+                });
+                ui.painter().add(mesh);
+            }
+
+            new_drag
+        } else {
+            None
+        };
+
+        InnerResponse::new(new_drag, response)
+    }
+
+    fn ui(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        let header = self.header.as_deref();
+        header_frame(id, header, false, contents, items, ui, |style, ui| {
+            // Reserve shape for the dragged item's shadow.
+            let shadow = ui.painter().add(egui::Shape::Noop);
+
+            let InnerResponse { inner, response } = self.body(id, contents, items, ui);
+            let min_rect = response.rect;
+            let n = N as f32;
+
+            let inner = match (contents.drag.as_ref(), inner) {
+                // We are dragging onto another item, check to see if the dragged item will fit anywhere within its contents.
+                (Some(drag), Some(ContentsResponse::NewTarget((id, slot, _)))) => {
+                    if contents.is_container(id) {
+                        let item = contents.items.get(id).expect("item exists").1;
+                        let target = contents.find_slot(id, drag.id, &drag.item, &drag.source);
+
+                        // The item shadow is the target item for drag-to-item, not the dragged item.
+                        let color = self.shadow_color(true, target.is_some(), ui);
+                        let center = min_rect.min + self.center(slot);
+                        let mesh = hex_mesh(&item.shape, min_rect, center, color, n);
+                        ui.painter().set(shadow, mesh);
+                        drop_preview(id, target, contents, drag, ui);
+
+                        target.map(|(c, slot)| ContentsResponse::ItemTarget((c, slot, ui.id()), id))
+                    } else {
+                        // Don't set target to non-contents.
+                        None
+                    }
+                }
+
+                // Dragging over an empty cell.
+                (Some(drag), None) => {
+                    let accepts = self.accepts(&drag.item);
+
+                    // Highlight the contents border if we can accept the dragged item.
+                    if accepts {
+                        style.bg_stroke = ui.visuals().widgets.hovered.bg_stroke;
+                    }
+
+                    // Target the cell under the center of the dragged item's first cell.
+                    let slot = ui
+                        .ctx()
+                        .pointer_latest_pos()
+                        .filter(|_| response.contains_pointer())
+                        .map(|p| {
+                            self.slot(p - min_rect.min - drag.offset + Self::cell_size() * 0.5)
+                        });
+
+                    let fits = slot
+                        .map(|slot| {
+                            self.fits(id, &drag.item, slot, &drag.source)
+                                && contents.fits_weight(id, drag.id)
+                        })
+                        .unwrap_or_default();
+
+                    // Paint the dragged item's shadow, showing which cells will be filled.
+                    if let Some(slot) = slot {
+                        let color = self.shadow_color(accepts, fits, ui);
+                        let center = min_rect.min + self.center(slot);
+                        let mesh = hex_mesh(&drag.item.shape, min_rect, center, color, n);
+                        ui.painter().set(shadow, mesh);
+                    }
+
+                    slot.filter(|_| accepts && fits)
+                        .map(|slot| ContentsResponse::NewTarget((id, slot, ui.id())))
+                }
+
+                (_, inner) => inner,
+            };
+
+            InnerResponse::new(inner, response)
+        })
+    }
+}
+
+/// Offset ("odd-r") to axial coordinates.
+fn to_axial(col: i32, row: i32) -> (i32, i32) {
+    (col - (row - (row & 1)) / 2, row)
+}
+
+/// Axial to offset ("odd-r") coordinates.
+fn to_offset((q, r): (i32, i32)) -> (i32, i32) {
+    (q + (r - (r & 1)) / 2, r)
+}
+
+/// Pixel offset of axial cell `(q, r)` from the center of cell `(0, 0)`, for cells `n` pixels wide.
+fn axial_to_pixel(q: f32, r: f32, n: f32) -> egui::Vec2 {
+    egui::vec2(n * (q + r * 0.5), n * SQRT_3 * 0.5 * r)
+}
+
+/// Axial cell containing the pixel offset `p` from the center of cell `(0, 0)`.
+fn pixel_to_axial(p: egui::Vec2, n: f32) -> (i32, i32) {
+    let r = p.y / (n * SQRT_3 * 0.5);
+    let q = p.x / n - r * 0.5;
+
+    // Round in cube coordinates, then fix up the component with the largest error.
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    } else if dr > ds {
+        rr = -rq - rs;
+    }
+    (rq as i32, rr as i32)
+}
+
+/// Cell centers of a hex item shape relative to the center of its first cell.
+fn shape_centers(shape: &Shape, n: f32) -> impl Iterator<Item = egui::Vec2> + '_ {
+    shape.slots().map(move |slot| {
+        let UVec2 { x, y } = shape.pos(slot);
+        axial_to_pixel(x as f32, y as f32, n)
+    })
+}
+
+/// Size in pixels of the bounds of a hex item shape.
+pub(crate) fn item_size(shape: &Shape, n: f32) -> egui::Vec2 {
+    let (w, h) = (shape.width() as f32, shape.height() as f32);
+    axial_to_pixel(w - 1.0, h - 1.0, n) + egui::vec2(n, n * 2.0 / SQRT_3)
+}
+
+/// Corners of a pointy-top hexagon `n` pixels wide.
+fn hexagon(center: Pos2, n: f32) -> [Pos2; 6] {
+    let radius = n / SQRT_3;
+    std::array::from_fn(|i| {
+        let a = (60.0 * i as f32 - 30.0).to_radians();
+        center + radius * egui::vec2(a.cos(), a.sin())
+    })
+}
+
+fn add_hexagon(mesh: &mut egui::Mesh, center: Pos2, color: Color32, n: f32) {
+    let i = mesh.vertices.len() as u32;
+    mesh.colored_vertex(center, color);
+    hexagon(center, n)
+        .into_iter()
+        .for_each(|p| mesh.colored_vertex(p, color));
+    (0..6).for_each(|k| mesh.add_triangle(i, i + 1 + k, i + 1 + (k + 1) % 6));
+}
+
+/// The hex counterpart to `shape_mesh`. `origin` is the center of the shape's first cell. Cells centered outside `clip` are skipped.
+pub fn hex_mesh(shape: &Shape, clip: Rect, origin: Pos2, color: Color32, n: f32) -> egui::Mesh {
+    let mut mesh = egui::Mesh::default();
+    shape_centers(shape, n)
+        .map(|c| origin + c)
+        .filter(|c| clip.contains(*c))
+        .for_each(|c| add_hexagon(&mut mesh, c, color, n));
+    mesh
+}
+
+/// Number of 60° steps in a hex item rotation. Square rotations don't apply.
+fn hex_steps(rotation: ItemRotation) -> usize {
+    match rotation {
+        ItemRotation::R60 => 1,
+        ItemRotation::R120 => 2,
+        ItemRotation::R180 => 3,
+        ItemRotation::R240 => 4,
+        ItemRotation::R300 => 5,
+        _ => 0,
+    }
+}
+
+/// Paints the icon of a hex item. `origin` is the center of the item's first cell.
+fn paint_icon<T>(item: &Item<T>, origin: Pos2, icon: TextureId, drag_scale: f32, n: f32, ui: &Ui) {
+    // The icon covers the bounds of the unrotated (but mirrored) shape. Rotate it about the centroid of the cells, which is the same for the rotated shape. Items inserted directly can have square rotations (see `ItemTransform`), which are undone like in a grid.
+    let shape = match hex_steps(item.rotation) {
+        0 => item.rotation.inverse().apply(&item.shape),
+        steps => (0..6 - steps).fold(item.shape.clone(), |s, _| s.rotate60()),
+    };
+    let centroid = |shape: &Shape| {
+        let (sum, count) = shape_centers(shape, n)
+            .fold((egui::Vec2::ZERO, 0.0), |(sum, count), c| {
+                (sum + c, count + 1.0)
+            });
+        sum / f32::max(count, 1.0)
+    };
+    let c0 = centroid(&shape);
+    let bounds = Rect::from_min_size(
+        (egui::vec2(n, n * 2.0 / SQRT_3) * -0.5).to_pos2(),
+        item_size(&shape, n),
+    );
+
+    // Scale down if dragging (see `Item::body`).
+    let scale = egui::lerp(1.0..=0.88, drag_scale);
+    let center = origin + centroid(&item.shape);
+    let rect = Rect::from_min_size(
+        center + (bounds.min.to_vec2() - c0) * scale,
+        bounds.size() * scale,
+    );
+
+    if ui.is_rect_visible(rect) {
+        let pivot = (c0 - bounds.min.to_vec2()) / bounds.size();
+        let image = egui::Image::new((icon, rect.size())).tint(Rgba::from_rgba_premultiplied(
+            1.0,
+            1.0,
+            1.0,
+            egui::lerp(1.0..=0.8, drag_scale),
+        ));
+
+        // Mirror first, then rotate (see `Item::body`).
+        let image = if item.mirror {
+            image.uv(Rect::from_min_max(
+                egui::pos2(1.0, 0.0),
+                egui::pos2(0.0, 1.0),
+            ))
+        } else {
+            image
+        };
+
+        image
+            .rotate(item.rotation.angle(), pivot)
+            .paint_at(ui, rect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn offset_axial() {
+        for (col, row) in [(0, 0), (3, 1), (2, 4), (-1, -1), (5, -3)] {
+            assert_eq!(to_offset(to_axial(col, row)), (col, row));
+        }
+    }
+
+    #[test]
+    fn cells() {
+        let hex = HexContents::<Flags>::new((3, 3));
+        let line = Shape::from_ones(2, [1, 1]);
+        assert_eq!(hex.cells(&line, 0), Some(vec![0, 1]));
+        assert_eq!(hex.cells(&line, 2), None);
+
+        // Each row of an item is shifted right half a cell, so a column runs diagonally down and to the right.
+        let column = Shape::from_ones(1, [1, 1, 1]);
+        assert_eq!(hex.cells(&column, 0), Some(vec![0, 3, 7]));
+        assert_eq!(hex.cells(&column, 1), Some(vec![1, 4, 8]));
+        assert_eq!(hex.cells(&column, 2), None);
+    }
+
+    #[test]
+    fn pixel_round_trip() {
+        for (q, r) in [(0, 0), (1, 0), (-2, 3), (4, -1)] {
+            let p = axial_to_pixel(q as f32, r as f32, 64.0);
+            assert_eq!(pixel_to_axial(p, 64.0), (q, r));
+            // Near the edge of the cell.
+            assert_eq!(pixel_to_axial(p + egui::vec2(25.0, 10.0), 64.0), (q, r));
+        }
+    }
+}
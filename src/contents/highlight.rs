@@ -0,0 +1,158 @@
+use bevy_ecs::entity::EntityHashMap;
+use bevy_egui::egui::Color32;
+
+use super::*;
+
+/// How a container (or section) relates to the dragged item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropTarget {
+    /// Accepts the item and has room for it.
+    Fits,
+    /// Accepts the item but has no room for it, or it's too heavy.
+    Full,
+    /// Doesn't accept the item, or is the item or inside it.
+    Rejects,
+}
+
+/// Background tints for every shown container while dragging, by `DropTarget` (see `Options::drop_highlight`). The tints are blended over the container background.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DropHighlight {
+    pub fits: Color32,
+    pub full: Color32,
+    pub rejects: Color32,
+}
+
+impl Default for DropHighlight {
+    fn default() -> Self {
+        Self {
+            fits: Color32::GREEN.gamma_multiply(0.1),
+            full: Color32::RED.gamma_multiply(0.1),
+            rejects: Color32::GRAY.gamma_multiply(0.1),
+        }
+    }
+}
+
+impl DropHighlight {
+    pub fn color(&self, target: DropTarget) -> Color32 {
+        match target {
+            DropTarget::Fits => self.fits,
+            DropTarget::Full => self.full,
+            DropTarget::Rejects => self.rejects,
+        }
+    }
+}
+
+/// Drop targets for the current drag, so they aren't computed for every container every frame.
+#[derive(Debug, Default)]
+pub struct DropTargets {
+    // The dragged item and its transform, since rotating or mirroring changes what fits.
+    key: Option<(Entity, ItemTransform)>,
+    targets: EntityHashMap<DropTarget>,
+}
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// How container `id` relates to the dragged item, or None if nothing is dragged. Only available with `Options::drop_highlight` or `Summary::fits` set.
+    pub fn drop_target(&self, id: Entity) -> Option<DropTarget> {
+        self.drag.as_ref()?;
+        self.drop_targets.targets.get(&id).copied()
+    }
+
+    // Recomputes the drop targets of every container with `accepts` and `find_slot` when a drag starts or the dragged item is rotated or mirrored, and clears them when it ends.
+    pub(crate) fn update_drop_targets(&mut self) {
+        if self.options.drop_highlight.is_none() && !self.options.summary.fits {
+            return;
+        }
+
+        let key = self.drag.as_ref().map(|d| (d.id, d.item.transform()));
+        if self.drop_targets.key == key {
+            return;
+        }
+
+        let targets = match self.drag.as_ref() {
+            Some(drag) => self
+                .nodes
+                .iter()
+                .filter_map(|id| self.get(id).map(|ci| (id, ci)))
+                .map(|(id, ci)| {
+                    let target = if id == drag.id
+                        || self.ancestors(id).any(|a| a == drag.id)
+                        || !ci.contents.accepts(&drag.item)
+                    {
+                        DropTarget::Rejects
+                    } else if self.fits_weight(id, drag.id)
+                        && ci
+                            .contents
+                            .find_slot(id, &drag.item, &drag.source)
+                            .is_some()
+                    {
+                        DropTarget::Fits
+                    } else {
+                        DropTarget::Full
+                    };
+                    (id, target)
+                })
+                .collect(),
+            None => EntityHashMap::default(),
+        };
+
+        *self.drop_targets = DropTargets { key, targets };
+    }
+
+    // Tints the frame of container `id` by its drop target, if enabled.
+    pub(crate) fn drop_tint(&self, id: Entity, style: &mut WidgetVisuals) {
+        if let Some((highlight, target)) = self.options.drop_highlight.zip(self.drop_target(id)) {
+            style.bg_fill = style.bg_fill.blend(highlight.color(target));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn drop_targets() {
+        let mut world = world_with(Options {
+            drop_highlight: Some(DropHighlight::default()),
+            ..Default::default()
+        });
+
+        let grid = |size: (u32, u32), flags| {
+            ContentsItems::new(GridContents::<Flags>::new(size).with_flags(flags))
+        };
+        let item = || (Name::new(""), Item::new(Flags::A));
+        let bag = world.spawn(grid((2, 1), Flags::A)).id();
+        let pouch = world.spawn((grid((1, 1), Flags::A), item())).id();
+        let chest = world.spawn(grid((1, 1), Flags::empty())).id();
+        let [stone, pebble] = [(); 2].map(|_| world.spawn(item()).id());
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, pouch);
+        storage.insert(pouch, pebble);
+        state.apply(&mut world);
+
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.update_drop_targets();
+        assert_eq!(storage.drop_target(bag), None);
+
+        let drag = |storage: &ContentsStorage<Flags>, id| {
+            DragItem::new(id, storage.items.get(id).unwrap().1.clone())
+        };
+        *storage.drag = Some(drag(&storage, stone));
+        storage.update_drop_targets();
+        let targets = [bag, pouch, chest].map(|id| storage.drop_target(id));
+        use DropTarget::*;
+        assert_eq!(targets, [Some(Fits), Some(Full), Some(Rejects)]);
+
+        // The dragged container can't hold itself.
+        *storage.drag = Some(drag(&storage, pouch));
+        storage.update_drop_targets();
+        assert_eq!(storage.drop_target(pouch), Some(Rejects));
+
+        *storage.drag = None;
+        storage.update_drop_targets();
+        assert_eq!(storage.drop_target(bag), None);
+    }
+}
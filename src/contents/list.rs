@@ -0,0 +1,415 @@
+use bevy_egui::egui::{
+    self, epaint::RectShape, Align2, CursorIcon, Modifiers, Rect, Sense, TextureId, Ui,
+};
+
+use super::*;
+
+/// Contains items in an ordered list, one item per row. Item shapes are ignored, so the capacity is a number of items. Items are always packed from the first slot: inserting or removing an item shifts the slots of the items after it.
+#[derive(Clone, Debug, Reflect)]
+pub struct ListContents<T, const N: usize = 32> {
+    pub header: Option<String>,
+    /// Maximum number of items.
+    pub capacity: usize,
+    /// Number of items in the list.
+    pub len: usize,
+    /// Flags determine what kinds of items will be accepted (see `Accepts`).
+    pub flags: T,
+}
+
+impl<T, const N: usize> ListContents<T, N>
+where
+    T: Accepts,
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity greater than zero");
+        Self {
+            header: None,
+            capacity,
+            len: 0,
+            flags: T::default(),
+        }
+    }
+
+    pub fn with_flags(mut self, flags: impl Into<T>) -> Self {
+        self.flags = flags.into();
+        self
+    }
+
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Single row dimensions in pixels.
+    pub const fn row_size() -> egui::Vec2 {
+        egui::vec2(N as f32 * 8.0, N as f32)
+    }
+
+    /// Returns the slot for an item dropped at `gap` (0 is before the first row, `len` after the last). If the item is moving within this list the slots after it shift up once it's removed.
+    pub fn drop_slot(&self, id: Entity, gap: usize, source: &DragSource) -> usize {
+        match source {
+            Some((source_id, slot, _)) if id == *source_id && gap > *slot => gap - 1,
+            _ => gap,
+        }
+    }
+
+    /// Paints a row: icon, name and, for containers, the number of items inside.
+    fn paint_row(row: Rect, name: &str, icon: TextureId, count: Option<usize>, ui: &Ui) {
+        let icon_rect = Rect::from_min_size(row.min, egui::Vec2::splat(N as f32));
+        egui::Image::new((icon, icon_rect.size())).paint_at(ui, icon_rect);
+
+        let font = egui::TextStyle::Body.resolve(ui.style());
+        ui.painter().text(
+            row.left_center() + egui::vec2(N as f32 + 4.0, 0.0),
+            Align2::LEFT_CENTER,
+            name,
+            font.clone(),
+            ui.visuals().text_color(),
+        );
+
+        if let Some(count) = count {
+            ui.painter().text(
+                row.right_center() - egui::vec2(4.0, 0.0),
+                Align2::RIGHT_CENTER,
+                count.to_string(),
+                font,
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+}
+
+impl<T: Accepts, const N: usize> Contents<T> for ListContents<T, N> {
+    fn slots(&self) -> usize {
+        self.capacity
+    }
+
+    fn used(&self) -> usize {
+        self.len
+    }
+
+    fn packed(&self) -> bool {
+        true
+    }
+
+    fn insert(&mut self, _slot: usize, _id: Entity, _item: &Item<T>) {
+        self.len += 1;
+    }
+
+    fn remove(&mut self, _slot: usize, _id: Entity, _item: &Item<T>) {
+        self.len = self.len.saturating_sub(1);
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn pos(&self, slot: usize) -> egui::Vec2 {
+        egui::vec2(0.0, slot as f32 * N as f32)
+    }
+
+    fn slot(&self, p: egui::Vec2) -> usize {
+        ((p.y / N as f32).max(0.0) as usize).min(self.len)
+    }
+
+    fn accepts(&self, item: &Item<T>) -> bool {
+        self.flags.accepts(&item.flags)
+    }
+
+    fn fits(&self, id: Entity, _item: &Item<T>, slot: usize, source: &DragSource) -> bool {
+        // When moving within one list, the item is removed before it's inserted again.
+        let len = match source {
+            Some((source_id, ..)) if id == *source_id => self.len.saturating_sub(1),
+            _ => self.len,
+        };
+
+        slot <= len && len < self.capacity
+    }
+
+    fn find_slot(
+        &self,
+        id: Entity,
+        item: &Item<T>,
+        source: &DragSource,
+    ) -> Option<(Entity, usize)> {
+        if !self.accepts(item) {
+            return None;
+        }
+
+        // Append.
+        let slot = match source {
+            Some((source_id, ..)) if id == *source_id => self.len.saturating_sub(1),
+            _ => self.len,
+        };
+
+        self.fits(id, item, slot, source).then_some((id, slot))
+    }
+
+    fn rotate(&self, _drag: &mut DragItem<T>) {
+        // Shapes are ignored.
+    }
+
+    fn paint(&self, contents: &ContentsStorage<T>, items: &[SlotItem], ui: &mut Ui) {
+        let rows = items.len().max(1) as f32;
+        let size = Self::row_size() * egui::vec2(1.0, rows);
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        if ui.is_rect_visible(rect) {
+            for (&SlotItem(slot, item_id), (name, _, icon)) in contents.items(items) {
+                let row = Rect::from_min_size(rect.min + self.pos(slot), Self::row_size());
+                let count = contents
+                    .is_container(item_id)
+                    .then(|| contents.members.get(item_id).map_or(0, |c| c.len()));
+                let icon = icon.map(|icon| icon.0).unwrap_or_default();
+                Self::paint_row(row, name, icon, count, ui);
+            }
+        }
+    }
+
+    fn body(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        assert!(items.len() <= self.slots());
+
+        // Always allocate at least one row so an empty list can be dropped on.
+        let rows = items.len().max(1) as f32;
+        let size = Self::row_size() * egui::vec2(1.0, rows);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+
+        let new_drag = if ui.is_rect_visible(rect) {
+            let p = ui.ctx().pointer_latest_pos();
+
+            contents
+                .items(items)
+                .filter_map(|(&SlotItem(slot, item_id), (name, item, icon))| {
+                    let eid = Id::new(item_id);
+                    let icon = icon.map(|icon| icon.0).unwrap_or_default();
+                    let row = Rect::from_min_size(rect.min + self.pos(slot), Self::row_size());
+                    let count = contents
+                        .is_container(item_id)
+                        .then(|| contents.members.get(item_id).map_or(0, |c| c.len()));
+
+                    let drag_id = contents.drag.as_ref().filter(|d| d.id == item_id);
+                    let drag_scale = ui.ctx().animate_bool(eid.with("scale"), drag_id.is_some());
+
+                    match drag_id {
+                        // This row is being dragged. Leave a gap where it was and draw it in a new area (see `Item::ui`).
+                        Some(drag) => {
+                            ui.output_mut(|o| o.cursor_icon = CursorIcon::Grab);
+
+                            if let Some(p) = p {
+                                egui::containers::Area::new(eid)
+                                    .fixed_pos(drag.origin.lerp(p - drag.outer_offset, drag_scale))
+                                    .interactable(false)
+                                    .show(ui.ctx(), |ui| {
+                                        let size = Self::row_size();
+                                        let (r, _) = ui.allocate_exact_size(size, Sense::hover());
+                                        Self::paint_row(r, name, icon, count, ui);
+                                    });
+                            }
+
+                            None
+                        }
+                        _ => {
+                            Self::paint_row(row, name, icon, count, ui);
+
+                            let p =
+                                p.filter(|p| response.contains_pointer() && row.contains(*p))?;
+
+                            if contents.drag.is_some() {
+                                // Dragging a different item? Only the middle of a container row targets the container. Otherwise the drop goes between rows.
+                                let t = (p.y - row.min.y) / row.height();
+                                return ((0.25..0.75).contains(&t)
+                                    && contents.is_container(item_id))
+                                .then(|| ContentsResponse::NewTarget((item_id, slot, ui.id())));
+                            }
+
+                            ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
+                            let response = ui.interact(row, eid, Sense::click_and_drag());
+                            let response = contents.hover_ui(item_id, item, name, response);
+
+                            if response.double_clicked() {
+                                Some(ContentsResponse::Open(item_id))
+                            } else if response.clicked()
+                                && ui.input(|i| i.modifiers.contains(Modifiers::CTRL))
+                            {
+                                Some(ContentsResponse::SendItem(DragItem::new(
+                                    item_id,
+                                    item.clone(),
+                                )))
+                            } else if response.drag_started() {
+                                let offset = p - row.min;
+                                Some(ContentsResponse::NewDrag(DragItem {
+                                    offset,
+                                    outer_offset: outer_offset(offset, row.size(), OUTER_DISTANCE),
+                                    origin: row.min,
+                                    offset_slot: slot,
+                                    ..DragItem::new(item_id, item.clone())
+                                }))
+                            } else {
+                                None
+                            }
+                        }
+                    }
+                    .map(|mut cr| {
+                        match cr {
+                            // Set source. Lists have no shape to cache.
+                            ContentsResponse::NewDrag(ref mut drag)
+                            | ContentsResponse::SendItem(ref mut drag) => {
+                                drag.source = Some((id, slot, Shape::new(Size::ONE, false)));
+                            }
+                            _ => (),
+                        }
+                        cr
+                    })
+                })
+                .at_most_one()
+                .unwrap_or_else(|mut e| {
+                    tracing::warn!("more than one item response");
+                    e.next()
+                })
+        } else {
+            None
+        };
+
+        InnerResponse::new(new_drag, response)
+    }
+
+    fn ui(
+        &self,
+        id: Entity,
+        contents: &ContentsStorage<T>,
+        items: &[SlotItem],
+        ui: &mut Ui,
+    ) -> InnerResponse<Option<ContentsResponse<T>>> {
+        let header = self.header.as_deref();
+        header_frame(id, header, false, contents, items, ui, |style, ui| {
+            // Reserve shape for the drag-to-item highlight.
+            let shadow = ui.painter().add(egui::Shape::Noop);
+
+            let InnerResponse { inner, response } = self.body(id, contents, items, ui);
+            let min_rect = response.rect;
+
+            let inner = match (contents.drag.as_ref(), inner) {
+                // We are dragging onto a container row, check to see if the dragged item will fit anywhere within its contents.
+                (Some(drag), Some(ContentsResponse::NewTarget((id, slot, _)))) => {
+                    let target = contents.find_slot(id, drag.id, &drag.item, &drag.source);
+
+                    let color = self.shadow_color(true, target.is_some(), ui);
+                    let row = Rect::from_min_size(min_rect.min + self.pos(slot), Self::row_size());
+                    ui.painter().set(shadow, RectShape::filled(row, 0.0, color));
+                    if contents.is_container(id) {
+                        drop_preview(id, target, contents, drag, ui);
+                    }
+
+                    target.map(|(c, slot)| ContentsResponse::ItemTarget((c, slot, ui.id()), id))
+                }
+
+                // Dragging between rows.
+                (Some(drag), None) => {
+                    let accepts = self.accepts(&drag.item);
+
+                    // Highlight the contents border if we can accept the dragged item.
+                    if accepts {
+                        style.bg_stroke = ui.visuals().widgets.hovered.bg_stroke;
+                    }
+
+                    // The gap between rows nearest the pointer.
+                    let gap = ui
+                        .ctx()
+                        .pointer_latest_pos()
+                        .filter(|_| response.contains_pointer())
+                        .map(|p| {
+                            let gap = ((p.y - min_rect.min.y) / N as f32).round().max(0.0);
+                            (gap as usize).min(items.len())
+                        });
+
+                    let slot = gap.map(|gap| self.drop_slot(id, gap, &drag.source));
+                    let fits = slot
+                        .map(|slot| {
+                            self.fits(id, &drag.item, slot, &drag.source)
+                                && contents.fits_weight(id, drag.id)
+                        })
+                        .unwrap_or_default();
+
+                    // Paint the insertion line on top of the rows.
+                    if let Some(gap) = gap {
+                        let color = self.shadow_color(accepts, fits, ui);
+                        let y = min_rect.min.y + gap as f32 * N as f32;
+                        let line = Rect::from_x_y_ranges(min_rect.x_range(), (y - 1.5)..=(y + 1.5));
+                        ui.painter().add(RectShape::filled(line, 0.0, color));
+                    }
+
+                    slot.filter(|_| accepts && fits)
+                        .map(|slot| ContentsResponse::NewTarget((id, slot, ui.id())))
+                }
+
+                (_, inner) => inner,
+            };
+
+            InnerResponse::new(inner, response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn insert_between() {
+        let mut world = world();
+
+        let list = ContentsItems::new(ListContents::<Flags>::new(3).with_flags(Flags::A));
+        let list_id = world.spawn(list).id();
+        let item = Item::new(Flags::A);
+        let [a, b, c] = ["a", "b", "c"].map(|n| world.spawn((Name::new(n), item.clone())).id());
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        for i in [a, b, c] {
+            storage.insert(list_id, i);
+        }
+        state.apply(&mut world);
+
+        // Drags `id` from `slot` to the gap before `gap`.
+        let mut drag = |id, slot, gap| {
+            let mut storage = state.get_mut(&mut world).unwrap();
+            let source = Some((list_id, slot, Shape::new(Size::ONE, false)));
+            let ContentsKind::List(list) = &storage.get(list_id).unwrap().contents else {
+                unreachable!()
+            };
+            let slot = list.drop_slot(list_id, gap, &source);
+            assert!(list.fits(list_id, &item, slot, &source));
+
+            let mut drag = DragItem::new(id, item.clone());
+            drag.source = source;
+            drag.target = Some((list_id, slot, Id::NULL));
+            storage.resolve_drag(drag);
+            state.apply(&mut world);
+
+            state.get_mut(&mut world).unwrap().slot_items(list_id)
+        };
+
+        // Move `c` before `b`.
+        assert_eq!(
+            drag(c, 2, 1),
+            [SlotItem(0, a), SlotItem(1, c), SlotItem(2, b)]
+        );
+
+        // Move `a` to the end: the gap after `b` is slot 2 once `a` is removed.
+        assert_eq!(
+            drag(a, 0, 3),
+            [SlotItem(0, c), SlotItem(1, b), SlotItem(2, a)]
+        );
+
+        // Full.
+        let storage = state.get_mut(&mut world).unwrap();
+        let list = &storage.get(list_id).unwrap().contents;
+        assert_eq!(list.find_slot(list_id, &item, &None), None);
+    }
+}
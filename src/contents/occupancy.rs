@@ -0,0 +1,97 @@
+use bevy_ecs::entity::{EntityMapper, MapEntities};
+
+use super::*;
+
+/// Which item fills each cell of a container, by slot. `GridContents` and `HexContents` keep one up to date in `Contents::insert` and `Contents::remove`, so finding the item in a cell doesn't mean overlaying the shape of every item. The entities are mapped when scenes are loaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Reflect)]
+pub struct Occupancy(Vec<Option<Entity>>);
+
+impl Occupancy {
+    /// An empty map for `len` cells.
+    pub fn new(len: usize) -> Self {
+        Self(vec![None; len])
+    }
+
+    /// The item filling `cell`, if any.
+    pub fn get(&self, cell: usize) -> Option<Entity> {
+        self.0.get(cell).copied().flatten()
+    }
+
+    /// Marks `cells` as filled by `id`.
+    pub fn fill(&mut self, cells: impl IntoIterator<Item = usize>, id: Entity) {
+        for cell in cells {
+            match self.0.get_mut(cell) {
+                Some(c) => *c = Some(id),
+                None => tracing::error!("occupancy cell {cell} out of range"),
+            }
+        }
+    }
+
+    /// Empties the `cells` filled by `id`. Cells filled by other items are left alone.
+    pub fn clear(&mut self, cells: impl IntoIterator<Item = usize>, id: Entity) {
+        for cell in cells {
+            if let Some(c) = self.0.get_mut(cell).filter(|c| **c == Some(id)) {
+                *c = None;
+            }
+        }
+    }
+
+    /// The distinct items filling any of `cells`, in the order found.
+    pub fn blocking(&self, cells: impl IntoIterator<Item = usize>) -> Vec<Entity> {
+        cells
+            .into_iter()
+            .filter_map(|cell| self.get(cell))
+            .unique()
+            .collect()
+    }
+}
+
+impl MapEntities for Occupancy {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for e in self.0.iter_mut().flatten() {
+            *e = entity_mapper.get_mapped(*e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn occupancy() {
+        let mut world = world();
+        world.add_observer(on_remove_item::<Flags>);
+
+        let bag = ContentsItems::new(GridContents::<Flags>::new((3, 2)).with_flags(Flags::A));
+        let bag = world.spawn(bag).id();
+        let item = |shape: Shape| (Name::new(""), Item::new(Flags::A).with_shape(shape));
+        let sword = world.spawn(item(Shape::new((3, 1), true))).id();
+        let stone = world.spawn(item(Shape::new((1, 1), true))).id();
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, sword);
+        storage.insert(bag, stone);
+        state.apply(&mut world);
+
+        let storage = state.get_mut(&mut world).unwrap();
+        let contents = &storage.get(bag).unwrap().contents;
+        let occupants = (0..6).map(|slot| contents.occupant(slot)).collect_vec();
+        let (s, t) = (Some(sword), Some(stone));
+        assert_eq!(occupants, [s, s, s, t, None, None]);
+
+        // A 2x2 item in the corner is blocked by both, in slot order.
+        let big = Item::new(Flags::A).with_shape(Shape::new((2, 2), true));
+        assert_eq!(contents.blocking(&big, 0), [sword, stone]);
+        assert_eq!(contents.blocking(&big, 4), []);
+
+        world.despawn(sword);
+        world.flush();
+        let storage = state.get_mut(&mut world).unwrap();
+        let contents = &storage.get(bag).unwrap().contents;
+        assert_eq!(contents.occupant(0), None);
+        assert_eq!(contents.blocking(&big, 0), [stone]);
+    }
+}
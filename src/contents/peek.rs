@@ -0,0 +1,60 @@
+use bevy_egui::egui::{emath::TSTransform, Order, Sense};
+
+use super::*;
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// Adds the hover text for item `id` to `response`. If `Options::hover_preview` is set and the item is a container, a preview of its contents is shown too (see `peek`).
+    pub fn hover_ui(&self, id: Entity, item: &Item<T>, name: &str, response: Response) -> Response {
+        let scale = self.options.hover_preview.filter(|_| self.is_container(id));
+        response.on_hover_ui_at_pointer(|ui| {
+            ui.label(item.hover_text(name, ui.style()));
+            if let Some(scale) = scale {
+                self.peek(id, scale, ui);
+            }
+        })
+    }
+
+    /// Shows the contents of container `id` and its sections, read-only and scaled by `scale`. The contents are painted with `Contents::paint` in an area of their own above `ui`, which is scaled when painted, so the space reserved in `ui` lags a frame behind.
+    pub fn peek(&self, id: Entity, scale: f32, ui: &mut Ui) {
+        let area_id = ui.id().with(("peek", id));
+        let size = ui.data(|d| d.get_temp::<Vec2>(area_id)).unwrap_or_default();
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+
+        let sections = self.sections.get(id).ok();
+        let layout = sections
+            .and_then(|s| s.0)
+            .unwrap_or(self.options.section_layout);
+
+        let area = egui::Area::new(area_id)
+            .order(Order::Tooltip)
+            .fixed_pos(rect.min)
+            .interactable(false)
+            .show(ui.ctx(), |ui| {
+                ui.with_layout(self.options.layout.to_egui_layout(), |ui| {
+                    self.peek_body(id, ui);
+                    ui.with_layout(layout.to_egui_layout(), |ui| {
+                        for &s in sections.iter().flat_map(|s| &s.1) {
+                            self.peek_body(s, ui);
+                        }
+                    });
+                });
+            });
+
+        // Scale around the top left corner, and keep it above the tooltip.
+        let layer = area.response.layer_id;
+        let transform = TSTransform::from_translation(rect.min.to_vec2() * (1.0 - scale))
+            * TSTransform::from_scaling(scale);
+        ui.ctx().set_transform_layer(layer, transform);
+        ui.ctx().move_to_top(layer);
+
+        let size = area.response.rect.size() * scale;
+        ui.data_mut(|d| d.insert_temp(area_id, size));
+    }
+
+    fn peek_body(&self, id: Entity, ui: &mut Ui) {
+        if let Some(ci) = self.get(id) {
+            let items = self.members_of(id).collect_vec();
+            ci.contents.paint(self, &items, ui);
+        }
+    }
+}
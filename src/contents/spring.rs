@@ -0,0 +1,188 @@
+use bevy_ecs::entity::EntityHashSet;
+
+use super::*;
+
+/// State for spring-loaded containers, which open when a dragged item dwells over them (see `Options::spring_delay`).
+#[derive(Debug, Default)]
+pub struct SpringLoad {
+    // The container item under the drag and when the drag got there.
+    hover: Option<(Entity, f64)>,
+    // Containers opened by dwelling, in the order opened.
+    opened: Vec<Entity>,
+    // When the drag left every target, if it has.
+    left: Option<f64>,
+    // Contents shown this egui pass and the last, so containers that are already open aren't opened (and later closed) again.
+    pass: u64,
+    shown: EntityHashSet,
+    shown_before: EntityHashSet,
+}
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    // Records that contents `id` were shown this pass.
+    pub(crate) fn spring_shown(&mut self, id: Entity, ctx: &egui::Context) {
+        let pass = ctx.cumulative_pass_nr();
+        let spring = &mut *self.spring;
+        if spring.pass != pass {
+            spring.pass = pass;
+            spring.shown_before = std::mem::take(&mut spring.shown);
+        }
+        spring.shown.insert(id);
+    }
+
+    // Tracks how long the drag has been over container item `hovered`, if any, and opens it once it's been there for the delay.
+    pub(crate) fn spring_hover(&mut self, hovered: Option<Entity>, ctx: &egui::Context) {
+        let Some(delay) = self.options.spring_delay else {
+            return;
+        };
+
+        let now = ctx.input(|i| i.time);
+        let spring = &mut *self.spring;
+        match (hovered, spring.hover) {
+            (Some(item), Some((h, since))) if h == item => {
+                if spring.opened.contains(&item)
+                    || spring.shown.contains(&item)
+                    || spring.shown_before.contains(&item)
+                {
+                    return;
+                }
+
+                let remaining = since + delay as f64 - now;
+                if remaining <= 0.0 {
+                    spring.opened.push(item);
+                    self.commands.trigger(ContainerOpen(item));
+                } else {
+                    // The pointer may be still, so make sure there's another pass.
+                    ctx.request_repaint_after_secs(remaining as f32);
+                }
+            }
+            (Some(item), _) => {
+                spring.hover = Some((item, now));
+                ctx.request_repaint_after_secs(delay);
+            }
+            (None, _) => spring.hover = None,
+        }
+    }
+
+    // Closes the containers opened by dwelling that the drag has left, i.e. those that don't hold `target`. With no target (the drag ended), all of them are closed.
+    pub(crate) fn spring_close(&mut self, target: Option<Entity>) {
+        let (keep, close): (Vec<_>, Vec<_>) = std::mem::take(&mut self.spring.opened)
+            .into_iter()
+            .partition(|&c| target.is_some_and(|t| t == c || self.contains(c, t)));
+
+        for c in close {
+            self.commands.trigger(ContainerClose(c));
+        }
+        self.spring.opened = keep;
+        if target.is_none() {
+            self.spring.hover = None;
+        }
+    }
+
+    // Closes every container opened by dwelling once the drag has been off all targets (`left`) for the delay, so crossing a gap on the way to an opened container doesn't close it.
+    pub(crate) fn spring_left(&mut self, left: bool, ctx: &egui::Context) {
+        let Some(delay) = self.options.spring_delay else {
+            return;
+        };
+
+        if !left || self.spring.opened.is_empty() {
+            self.spring.left = None;
+            return;
+        }
+
+        let now = ctx.input(|i| i.time);
+        let since = *self.spring.left.get_or_insert(now);
+        let remaining = since + delay as f64 - now;
+        if remaining <= 0.0 {
+            self.spring_close(None);
+            self.spring.left = None;
+        } else {
+            ctx.request_repaint_after_secs(remaining as f32);
+        }
+    }
+
+    // Ends spring loading when the drag ends. Containers holding the drop `target` stay open, and the rest are closed.
+    pub(crate) fn spring_end(&mut self, target: Option<Entity>) {
+        self.spring_close(target);
+
+        // The ones left open are the player's now.
+        let spring = &mut *self.spring;
+        spring.opened.clear();
+        spring.hover = None;
+        spring.left = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn spring() {
+        #[derive(Resource, Default)]
+        struct Events(Vec<(&'static str, Entity)>);
+
+        let mut world = world_with(Options {
+            spring_delay: Some(0.0),
+            ..Default::default()
+        });
+        world.init_resource::<Events>();
+        world.add_observer(|e: On<ContainerOpen>, mut events: ResMut<Events>| {
+            events.0.push(("open", e.0))
+        });
+        world.add_observer(|e: On<ContainerClose>, mut events: ResMut<Events>| {
+            events.0.push(("close", e.0))
+        });
+
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((2, 2)).with_flags(Flags::A));
+        let item = || (Name::new(""), Item::new(Flags::A));
+        let [ground, bag] = [(); 2].map(|_| world.spawn((grid(), item())).id());
+        let stone = world.spawn(item()).id();
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, stone);
+        state.apply(&mut world);
+
+        // Dwell over the bag until it opens. Already shown contents aren't opened.
+        let ctx = egui::Context::default();
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.spring_shown(ground, &ctx);
+        for hovered in [ground, ground, bag, bag, bag] {
+            storage.spring_hover(Some(hovered), &ctx);
+        }
+
+        // Moving inside keeps it open, and moving out closes it.
+        storage.spring_close(Some(stone));
+        storage.spring_close(Some(ground));
+        storage.spring_close(None);
+        state.apply(&mut world);
+        assert_eq!(
+            world.resource::<Events>().0,
+            [("open", bag), ("close", bag)]
+        );
+
+        // Leaving every target closes it after the delay.
+        let mut storage = state.get_mut(&mut world).unwrap();
+        for hovered in [ground, bag, bag] {
+            storage.spring_hover(Some(hovered), &ctx);
+        }
+        storage.spring_left(true, &ctx);
+        state.apply(&mut world);
+        assert_eq!(
+            world.resource::<Events>().0[2..],
+            [("open", bag), ("close", bag)]
+        );
+
+        // Dropping inside keeps it open.
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.spring_hover(None, &ctx);
+        for hovered in [bag, bag] {
+            storage.spring_hover(Some(hovered), &ctx);
+        }
+        storage.spring_end(Some(bag));
+        storage.spring_left(true, &ctx);
+        state.apply(&mut world);
+        assert_eq!(world.resource::<Events>().0[4..], [("open", bag)]);
+    }
+}
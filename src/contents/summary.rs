@@ -0,0 +1,183 @@
+use bevy_egui::egui::{ProgressBar, RichText};
+
+use super::*;
+
+/// Width in pixels of the fill bar shown in container headers (see `Summary::bar`).
+pub const FILL_BAR_WIDTH: f32 = 48.0;
+
+/// Extra information shown in container headers (see `Options::summary`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// Show used and total slots after the header, e.g. "Backpack 14/24", followed by the number of items and, if there are more items nested in containers inside, the total, e.g. "3 items (5 total)".
+    pub text: bool,
+    /// Show a bar filled in proportion to the used slots.
+    pub bar: bool,
+    /// While dragging, show whether the dragged item fits anywhere in each container (see `ContentsStorage::can_fit`).
+    pub fits: bool,
+}
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// Used and total slots of container `id` and its sections.
+    pub fn usage(&self, id: Entity) -> (usize, usize) {
+        let sections = self.sections.get(id).into_iter().flat_map(|s| &s.1);
+        std::iter::once(id)
+            .chain(sections.copied())
+            .filter_map(|id| self.get(id))
+            .fold((0, 0), |(used, total), ci| {
+                (used + ci.contents.used(), total + ci.contents.slots())
+            })
+    }
+
+    /// Summary for the header of container `id`, e.g. "14/24, 3 items (5 total)", if enabled with `Summary::text`.
+    pub fn summary_text(&self, id: Entity) -> Option<String> {
+        if !self.options.summary.text {
+            return None;
+        }
+
+        let (used, total) = self.usage(id);
+        // Items in sections count as items of the container.
+        let sections = self
+            .sections
+            .get(id)
+            .map(|s| s.1.as_slice())
+            .unwrap_or_default();
+        let (items, nested) = self
+            .walk_all(id)
+            .fold((0, 0), |(items, nested), (_, step)| {
+                let Some(step) = step else {
+                    return (items, nested);
+                };
+                let direct = step.container == id || sections.contains(&step.container);
+                (items + usize::from(direct), nested + 1)
+            });
+
+        let plural = |n| if n == 1 { "item" } else { "items" };
+        let mut text = format!("{used}/{total}, {items} {}", plural(items));
+        if nested > items {
+            text.push_str(&format!(" ({nested} total)"));
+        }
+        Some(text)
+    }
+
+    /// Returns whether the dragged item fits anywhere in container `id` (or its sections), or None if nothing is dragged or `id` is the dragged item or inside it. Uses the drop targets (see `drop_target`), so it's only available with `Summary::fits` or `Options::drop_highlight` set.
+    pub fn can_fit(&self, id: Entity) -> Option<bool> {
+        self.summary_target(id).map(|t| t == DropTarget::Fits)
+    }
+
+    // The best drop target of container `id` and its sections, for the fits badge.
+    fn summary_target(&self, id: Entity) -> Option<DropTarget> {
+        let drag = self.drag.as_ref()?;
+        if drag.id == id || self.ancestors(id).any(|a| a == drag.id) {
+            return None;
+        }
+
+        let sections = self.sections.get(id).into_iter().flat_map(|s| &s.1);
+        std::iter::once(id)
+            .chain(sections.copied())
+            .filter_map(|id| self.drop_target(id))
+            .min_by_key(|t| match t {
+                DropTarget::Fits => 0,
+                DropTarget::Full => 1,
+                DropTarget::Rejects => 2,
+            })
+    }
+
+    // Header line for container `id`: the header text, fill bar and fits badge, as enabled.
+    pub(crate) fn header_ui(&self, id: Entity, header: &str, ui: &mut Ui) {
+        let summary = self.options.summary;
+        let fits = summary.fits.then(|| self.summary_target(id)).flatten();
+        if header.is_empty() && !summary.bar && fits.is_none() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if !header.is_empty() {
+                ui.label(header);
+            }
+
+            if summary.bar {
+                let (used, total) = self.usage(id);
+                if total > 0 {
+                    ui.add(
+                        ProgressBar::new(used as f32 / total as f32).desired_width(FILL_BAR_WIDTH),
+                    );
+                }
+            }
+
+            // Colored like the shadow of the dragged item.
+            if let Some((target, ci)) = fits.zip(self.get(id)) {
+                let fits = target == DropTarget::Fits;
+                let text = if fits { "✔ fits" } else { "✖ no room" };
+                let color = ci
+                    .contents
+                    .shadow_color(target != DropTarget::Rejects, fits, ui);
+                ui.label(RichText::new(text).small().color(color));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn summary() {
+        let mut world = world_with(Options {
+            summary: Summary {
+                text: true,
+                fits: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let grid = |size: (u32, u32)| {
+            ContentsItems::new(GridContents::<Flags>::new(size).with_flags(Flags::A))
+        };
+        let item = |shape: Shape| (Name::new(""), Item::new(Flags::A).with_shape(shape));
+        let section = world.spawn(grid((2, 1))).id();
+        let bag = world
+            .spawn((grid((3, 2)), Sections(None, vec![section])))
+            .id();
+        let pouch = world
+            .spawn((grid((1, 1)), item(Shape::new((2, 1), true))))
+            .id();
+        let [stone, pebble] = [(); 2].map(|_| world.spawn(item(Shape::new((1, 1), true))).id());
+        let sword = world.spawn(item(Shape::new((3, 1), true))).id();
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, pouch);
+        storage.insert(section, stone);
+        storage.insert(pouch, pebble);
+        state.apply(&mut world);
+
+        let mut storage = state.get_mut(&mut world).unwrap();
+        assert_eq!(storage.usage(bag), (3, 8));
+        assert_eq!(
+            storage.summary_text(bag).as_deref(),
+            Some("3/8, 2 items (3 total)")
+        );
+        assert_eq!(storage.summary_text(pouch).as_deref(), Some("1/1, 1 item"));
+
+        // The sword fits the bag, but not the full pouch.
+        assert_eq!(storage.can_fit(bag), None);
+        *storage.drag = Some(DragItem::new(
+            sword,
+            storage.items.get(sword).unwrap().1.clone(),
+        ));
+        storage.update_drop_targets();
+        assert_eq!(storage.can_fit(bag), Some(true));
+        assert_eq!(storage.can_fit(pouch), Some(false));
+
+        // Nothing fits inside the dragged item.
+        *storage.drag = Some(DragItem::new(
+            pouch,
+            storage.items.get(pouch).unwrap().1.clone(),
+        ));
+        storage.update_drop_targets();
+        assert_eq!(storage.can_fit(pouch), None);
+    }
+}
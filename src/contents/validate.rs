@@ -0,0 +1,421 @@
+use bevy_ecs::entity::EntityHashSet;
+
+use super::*;
+
+/// A problem found by `ContentsStorage::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// `item` doesn't fit in `container` at `slot`: it overlaps another item or is out of bounds.
+    Overlap {
+        container: Entity,
+        item: Entity,
+        slot: usize,
+    },
+    /// The stored occupancy of `container` doesn't match its items.
+    Occupancy { container: Entity },
+    /// The sections of `container` refer to `entity`, which is missing or has no contents.
+    Missing { container: Entity, entity: Entity },
+    /// `item` is in `container` but has no `Item` (or `Name`).
+    NotAnItem { container: Entity, item: Entity },
+    /// `container` holds items but has no contents.
+    NotAContainer { container: Entity },
+    /// `entity` is held in more than one place, as an item and/or a section.
+    Duplicate { entity: Entity },
+    /// `item` is inside itself.
+    Cycle { item: Entity },
+    /// `slot` is out of range for `container`, or out of order for packed contents.
+    BadSlot {
+        container: Entity,
+        item: Entity,
+        slot: usize,
+    },
+    /// `container` doesn't accept `item`.
+    NotAccepted { container: Entity, item: Entity },
+}
+
+/// Marks items being detached by `ContentsStorage::repair`, or despawned by a failed `Persist::load` or along with their container by `ContentsStorage::despawn`, so `on_remove_item` doesn't unpaint them or fire `ItemRemove`.
+#[derive(Component)]
+pub struct Detached;
+
+/// What `ContentsStorage::repair_subset` did.
+#[derive(Debug, Default)]
+pub struct Repaired {
+    /// The problems that were fixed.
+    pub problems: Vec<Problem>,
+    /// Items that were left outside of any container.
+    pub detached: Vec<Entity>,
+}
+
+// Contents rebuilt from their items.
+struct Rebuild<T: Accepts> {
+    contents: ContentsKind<T>,
+    problems: Vec<Problem>,
+    /// Items that were placed, with their old and new slots.
+    placed: Vec<(Entity, usize, usize)>,
+    /// Items that don't belong.
+    evicted: Vec<Entity>,
+}
+
+// Compares the state that `Contents::insert` and `Contents::remove` change.
+fn same_occupancy<T: Accepts>(a: &ContentsKind<T>, b: &ContentsKind<T>) -> bool {
+    match (a, b) {
+        (ContentsKind::Grid(a), ContentsKind::Grid(b)) => {
+            a.shape == b.shape && a.occupancy == b.occupancy
+        }
+        (ContentsKind::Hex(a), ContentsKind::Hex(b)) => {
+            a.shape == b.shape && a.occupancy == b.occupancy
+        }
+        (ContentsKind::List(a), ContentsKind::List(b)) => a.len == b.len,
+        (ContentsKind::Custom(a), ContentsKind::Custom(b)) => {
+            a.reflect_partial_eq(b.as_partial_reflect()).unwrap_or(true)
+        }
+        _ => false,
+    }
+}
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// Checks every inventory for problems, e.g. after loading a scene. See `repair`.
+    pub fn validate(&self) -> Vec<Problem> {
+        self.validate_subset(&self.nodes.iter().collect_vec())
+    }
+
+    /// Like `validate`, but only checks `entities` (e.g. the ones just loaded) and the sections and items they hold.
+    pub fn validate_subset(&self, entities: &[Entity]) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut seen = EntityHashSet::default();
+
+        for &e in entities {
+            if let Ok(s) = self.sections.get(e) {
+                for &section in &s.1 {
+                    if !seen.insert(section) {
+                        problems.push(Problem::Duplicate { entity: section });
+                    }
+
+                    if self.get(section).is_none() && self.sections.get(section).is_err() {
+                        problems.push(Problem::Missing {
+                            container: e,
+                            entity: section,
+                        });
+                    }
+                }
+            }
+
+            if self.in_container.contains(e) {
+                if !seen.insert(e) {
+                    problems.push(Problem::Duplicate { entity: e });
+                }
+
+                if self.in_cycle(e) {
+                    problems.push(Problem::Cycle { item: e });
+                }
+            }
+
+            if self.members.contains(e) && self.get(e).is_none() {
+                problems.push(Problem::NotAContainer { container: e });
+            }
+        }
+
+        for &e in entities {
+            if let Some(ci) = self.get(e) {
+                let rebuild = self.rebuild(e, &ci.contents, &EntityHashSet::default());
+                problems.extend(rebuild.problems);
+                if !same_occupancy(&rebuild.contents, &ci.contents) {
+                    problems.push(Problem::Occupancy { container: e });
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Fixes the problems found by `validate` (which are returned): occupancy is rebuilt from items, packed slots are renumbered, missing and duplicate sections are dropped, and items that don't belong are moved to `overflow`. Items that don't fit in `overflow` are left outside of any container. Changes are made with commands.
+    pub fn repair(&mut self, overflow: Entity) -> Vec<Problem> {
+        let nodes = self.nodes.iter().collect_vec();
+        self.repair_subset(&nodes, Some(overflow)).problems
+    }
+
+    /// Like `repair`, but only fixes `entities` (see `validate_subset`). Without `overflow`, items that don't belong are left outside of any container.
+    pub fn repair_subset(&mut self, entities: &[Entity], overflow: Option<Entity>) -> Repaired {
+        let problems = self.validate_subset(entities);
+
+        // Break cycles by evicting the items in them.
+        let cycles: EntityHashSet = problems
+            .iter()
+            .filter_map(|p| match p {
+                Problem::Cycle { item } => Some(*item),
+                _ => None,
+            })
+            .collect();
+
+        let mut evicted = Vec::new();
+        let mut in_sections = EntityHashSet::default();
+
+        for &e in entities {
+            if let Ok(s) = self.sections.get(e) {
+                let sections =
+                    s.1.iter()
+                        .copied()
+                        .filter(|s| {
+                            (self.get(*s).is_some() || self.sections.contains(*s))
+                                && !self.in_container.contains(*s)
+                                && in_sections.insert(*s)
+                        })
+                        .collect_vec();
+
+                if sections != s.1 {
+                    let layout = s.0;
+                    self.commands.entity(e).insert(Sections(layout, sections));
+                }
+            }
+
+            match self.get(e).map(|ci| self.rebuild(e, &ci.contents, &cycles)) {
+                Some(rebuild) => {
+                    for (id, slot, new_slot) in rebuild.placed {
+                        if slot != new_slot {
+                            self.commands.entity(id).insert(InContainer {
+                                container: e,
+                                slot: new_slot,
+                            });
+                        }
+                    }
+                    evicted.extend(rebuild.evicted);
+                    self.contents.get_mut(e).unwrap().contents = rebuild.contents;
+                }
+                None => {
+                    if let Ok(members) = self.members.get(e) {
+                        evicted.extend(members.iter());
+                    }
+                }
+            }
+        }
+
+        let mut detached = Vec::new();
+        for id in evicted {
+            let found = overflow
+                .zip(self.items.get(id).ok())
+                .and_then(|(overflow, (_, item, _))| self.find_slot(overflow, id, item, &None));
+
+            match found {
+                Some((container, slot)) => {
+                    let item = self.items.get(id).unwrap().1;
+                    self.contents
+                        .get_mut(container)
+                        .unwrap()
+                        .contents
+                        .insert(slot, id, item);
+                    self.commands
+                        .entity(id)
+                        .insert(InContainer { container, slot });
+                }
+                None => {
+                    tracing::warn!("no room in overflow for {id}");
+                    self.commands
+                        .entity(id)
+                        .insert(Detached)
+                        .remove::<(InContainer, Detached)>();
+                    detached.push(id);
+                }
+            }
+        }
+
+        Repaired { problems, detached }
+    }
+
+    // Checks if `id` is its own ancestor.
+    fn in_cycle(&self, id: Entity) -> bool {
+        self.ancestors(id).any(|a| a == id)
+    }
+
+    // Rebuilds the occupancy of `container` by inserting its items into empty contents, in slot order. Items in `skip` are evicted.
+    fn rebuild(
+        &self,
+        container: Entity,
+        contents: &ContentsKind<T>,
+        skip: &EntityHashSet,
+    ) -> Rebuild<T> {
+        let mut contents = contents.clone();
+        contents.clear();
+
+        let mut rebuild = Rebuild {
+            contents,
+            problems: Vec::new(),
+            placed: Vec::new(),
+            evicted: Vec::new(),
+        };
+
+        for SlotItem(slot, id) in self.slot_items(container) {
+            if skip.contains(&id) {
+                rebuild.evicted.push(id);
+                continue;
+            }
+
+            let contents = &mut rebuild.contents;
+            let Ok((_, item, _)) = self.items.get(id) else {
+                rebuild.problems.push(Problem::NotAnItem {
+                    container,
+                    item: id,
+                });
+                rebuild.evicted.push(id);
+                continue;
+            };
+
+            // Packed items are renumbered rather than evicted.
+            let new_slot = if contents.packed() {
+                rebuild.placed.len()
+            } else {
+                slot
+            };
+
+            let problem = if new_slot >= contents.slots() {
+                Some(Problem::BadSlot {
+                    container,
+                    item: id,
+                    slot,
+                })
+            } else if !contents.accepts(item) {
+                Some(Problem::NotAccepted {
+                    container,
+                    item: id,
+                })
+            } else if !contents.fits(container, item, new_slot, &None) {
+                Some(Problem::Overlap {
+                    container,
+                    item: id,
+                    slot,
+                })
+            } else {
+                None
+            };
+
+            match problem {
+                Some(problem) => {
+                    rebuild.problems.push(problem);
+                    rebuild.evicted.push(id);
+                }
+                None => {
+                    if new_slot != slot {
+                        rebuild.problems.push(Problem::BadSlot {
+                            container,
+                            item: id,
+                            slot,
+                        });
+                    }
+                    contents.insert(new_slot, id, item);
+                    rebuild.placed.push((id, slot, new_slot));
+                }
+            }
+        }
+
+        rebuild
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn validate_and_repair() {
+        let mut world = world();
+        world.add_observer(on_remove_item::<Flags>);
+
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((2, 1)).with_flags(Flags::A));
+        let item = |flags| (Name::new(""), Item::new(flags));
+        let missing = world.spawn_empty().id();
+        world.despawn(missing);
+        let bag = world.spawn((grid(), Sections(None, vec![missing]))).id();
+        let overflow = ContentsItems::new(ListContents::<Flags>::new(5).with_flags(Flags::A));
+        let overflow = world.spawn(overflow).id();
+        let a = world.spawn(item(Flags::A)).id();
+
+        let mut state = state(&mut world);
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.insert(bag, a);
+        state.apply(&mut world);
+
+        // Hand-edited: overlapping, unaccepted, out of range, and a stray painted slot.
+        let mut spawn = |flags, container, slot| {
+            world
+                .spawn((item(flags), InContainer { container, slot }))
+                .id()
+        };
+        let b = spawn(Flags::A, bag, 0);
+        let c = spawn(Flags::B, bag, 1);
+        let d = spawn(Flags::A, bag, 5);
+        let mut ci = world.get_mut::<ContentsItems<Flags>>(bag).unwrap();
+        let ContentsKind::Grid(g) = &mut ci.contents else {
+            unreachable!()
+        };
+        g.shape.paint(&Shape::new([1, 1], true), 1);
+
+        // Two containers inside each other.
+        let x = world.spawn((item(Flags::A), grid())).id();
+        let y = world
+            .spawn((
+                item(Flags::A),
+                grid(),
+                InContainer {
+                    container: x,
+                    slot: 0,
+                },
+            ))
+            .id();
+        world.entity_mut(x).insert(InContainer {
+            container: y,
+            slot: 0,
+        });
+
+        let storage = state.get_mut(&mut world).unwrap();
+        let problems = storage.validate();
+        for p in [
+            Problem::Overlap {
+                container: bag,
+                item: b,
+                slot: 0,
+            },
+            Problem::NotAccepted {
+                container: bag,
+                item: c,
+            },
+            Problem::BadSlot {
+                container: bag,
+                item: d,
+                slot: 5,
+            },
+            Problem::Occupancy { container: bag },
+            Problem::Missing {
+                container: bag,
+                entity: missing,
+            },
+            Problem::Cycle { item: x },
+            Problem::Cycle { item: y },
+            // Neither was painted.
+            Problem::Occupancy { container: x },
+            Problem::Occupancy { container: y },
+        ] {
+            assert!(problems.contains(&p), "{p:?} in {problems:?}");
+        }
+        assert_eq!(problems.len(), 9);
+
+        let mut storage = state.get_mut(&mut world).unwrap();
+        storage.repair(overflow);
+        state.apply(&mut world);
+
+        let storage = state.get_mut(&mut world).unwrap();
+        assert_eq!(storage.validate(), []);
+        assert_eq!(storage.slot_items(bag), [SlotItem(0, a)]);
+        // The overflow doesn't accept `c`, so it's left outside.
+        let mut moved = storage
+            .slot_items(overflow)
+            .into_iter()
+            .map(|SlotItem(_, i)| i)
+            .collect_vec();
+        moved.sort();
+        let mut expected = vec![b, d, x, y];
+        expected.sort();
+        assert_eq!(moved, expected);
+        assert_eq!(storage.parent(c), None);
+        assert!(world.get::<Detached>(c).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+use bevy_ecs::entity::EntityHashSet;
+
+use super::*;
+
+/// An item found by `ContentsStorage::walk`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalkItem {
+    pub id: Entity,
+    /// Slot in the container the item is in (the last entry of `path`).
+    pub slot: usize,
+    /// Containers (and sections) from the root down to the item's container.
+    pub path: Vec<Entity>,
+}
+
+impl WalkItem {
+    /// Zero for items in the root container.
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+// Containers are stored once, in the order visited, and nodes refer to them by index so paths are only built for `ContentsStorage::walk`.
+enum Node {
+    Container(Entity, Option<usize>),
+    Section(Entity, usize),
+    Item(Entity, usize, usize),
+}
+
+/// Where an item was found by `Walk`: its slot, the container it's in, and the index of that container in the walk (see `Walk::path`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Step {
+    pub slot: usize,
+    pub container: Entity,
+    pub index: usize,
+}
+
+/// Depth-first iterator over contents and sections. See `ContentsStorage::walk`.
+pub(crate) struct Walk<'a, 'w, 's, T: Accepts> {
+    storage: &'a ContentsStorage<'w, 's, T>,
+    stack: Vec<Node>,
+    // Containers visited so far, with the index of their parent.
+    containers: Vec<(Entity, Option<usize>)>,
+    // Containers are only visited once, in case of cycles.
+    visited: EntityHashSet,
+}
+
+impl<T: Accepts> Walk<'_, '_, '_, T> {
+    // Containers from the root down to the container at `index`.
+    pub(crate) fn path(&self, index: usize) -> Vec<Entity> {
+        let mut path = std::iter::successors(Some(index), |i| self.containers[*i].1)
+            .map(|i| self.containers[i].0)
+            .collect_vec();
+        path.reverse();
+        path
+    }
+}
+
+impl<T: Accepts> Iterator for Walk<'_, '_, '_, T> {
+    /// Items are returned with where they were found, sections without.
+    type Item = (Entity, Option<Step>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                Node::Container(id, parent) => {
+                    if !self.visited.insert(id) {
+                        continue;
+                    }
+                    let index = self.containers.len();
+                    self.containers.push((id, parent));
+
+                    // Push in reverse so items come out in slot order, followed by sections.
+                    if let Ok(s) = self.storage.sections.get(id) {
+                        self.stack
+                            .extend(s.1.iter().rev().map(|s| Node::Section(*s, index)));
+                    }
+
+                    self.stack.extend(
+                        self.storage
+                            .slot_items(id)
+                            .into_iter()
+                            .rev()
+                            .map(|SlotItem(slot, i)| Node::Item(i, slot, index)),
+                    );
+                }
+                Node::Section(id, parent) => {
+                    self.stack.push(Node::Container(id, Some(parent)));
+                    return Some((id, None));
+                }
+                Node::Item(id, slot, index) => {
+                    // The item's contents come next.
+                    self.stack.push(Node::Container(id, Some(index)));
+                    let container = self.containers[index].0;
+                    return Some((
+                        id,
+                        Some(Step {
+                            slot,
+                            container,
+                            index,
+                        }),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl<'w, 's, T: Accepts> ContentsStorage<'w, 's, T> {
+    // Walks sections as well as items.
+    pub(crate) fn walk_all(&self, root: Entity) -> Walk<'_, 'w, 's, T> {
+        Walk {
+            storage: self,
+            stack: vec![Node::Container(root, None)],
+            containers: Vec::new(),
+            visited: EntityHashSet::default(),
+        }
+    }
+
+    /// Depth-first iterator over every item in `root`, including items in sections and nested containers. Each item is followed by its own contents.
+    pub fn walk(&self, root: Entity) -> impl Iterator<Item = WalkItem> + '_ {
+        let mut walk = self.walk_all(root);
+        std::iter::from_fn(move || loop {
+            if let (id, Some(step)) = walk.next()? {
+                return Some(WalkItem {
+                    id,
+                    slot: step.slot,
+                    path: walk.path(step.index),
+                });
+            }
+        })
+    }
+
+    /// The container (or, for sections, the owner) of `id`.
+    pub fn parent(&self, id: Entity) -> Option<Entity> {
+        self.in_container
+            .get(id)
+            .map(|c| c.container)
+            .or_else(|_| self.section_of.get(id).map(|s| s.0))
+            .ok()
+    }
+
+    /// Iterates the containers above `id`, nearest first. Stops if a container repeats (see `validate`).
+    pub fn ancestors(&self, id: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let mut seen = EntityHashSet::default();
+        std::iter::successors(self.parent(id), |c| self.parent(*c))
+            .take_while(move |c| seen.insert(*c))
+    }
+
+    /// Containers from the root down to (and not including) `id`, e.g. for breadcrumbs.
+    pub fn path(&self, id: Entity) -> Vec<Entity> {
+        let mut path = self.ancestors(id).collect_vec();
+        path.reverse();
+        path
+    }
+
+    // Items in `root` (recursively), without their paths.
+    pub(crate) fn walk_items(&self, root: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.walk_all(root)
+            .filter_map(|(id, step)| step.map(|_| id))
+    }
+
+    /// Counts the items in `root` (recursively) matching `predicate`.
+    pub fn count_where(&self, root: Entity, predicate: impl Fn(Entity, &Item<T>) -> bool) -> usize {
+        self.walk_items(root)
+            .filter(|id| {
+                self.items
+                    .get(*id)
+                    .is_ok_and(|(_, item, _)| predicate(*id, item))
+            })
+            .count()
+    }
+
+    /// Sums `f` over the items in `root` (recursively).
+    pub fn sum<S: std::iter::Sum>(&self, root: Entity, f: impl Fn(Entity, &Item<T>) -> S) -> S {
+        self.walk_items(root)
+            .filter_map(|id| self.items.get(id).ok().map(|(_, item, _)| f(id, item)))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn walk() {
+        let mut world = world();
+
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((4, 4)));
+        let section = world.spawn(grid()).id();
+        let bag = world.spawn((grid(), Sections(None, vec![section]))).id();
+
+        let mut spawn = |flags, container, slot| {
+            let item = (Name::new(""), Item::new(flags));
+            world.spawn((item, InContainer { container, slot })).id()
+        };
+
+        // Spawned out of order to check that items are walked by slot.
+        let sword = spawn(Flags::B, bag, 5);
+        let pouch = spawn(Flags::B, bag, 0);
+        let stone = spawn(Flags::A, pouch, 0);
+        let potion = spawn(Flags::A, section, 0);
+        world.entity_mut(pouch).insert(grid());
+
+        let mut state = state(&mut world);
+        let storage = state.get_mut(&mut world).unwrap();
+
+        let walk = storage
+            .walk(bag)
+            .map(|w| (w.id, w.slot, w.depth(), w.path))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            walk,
+            [
+                (pouch, 0, 0, vec![bag]),
+                (stone, 0, 1, vec![bag, pouch]),
+                (sword, 5, 0, vec![bag]),
+                (potion, 0, 1, vec![bag, section]),
+            ]
+        );
+
+        assert!(storage.contains(bag, section));
+        assert!(storage.contains(bag, stone));
+        assert!(!storage.contains(pouch, potion));
+
+        assert_eq!(storage.count_where(bag, |_, i| i.flags == Flags::A), 2);
+        assert_eq!(storage.count_where(pouch, |_, i| i.flags == Flags::A), 1);
+        assert_eq!(storage.sum(bag, |_, i| i.flags.bits() as u32), 6);
+    }
+
+    #[test]
+    fn cycle() {
+        let mut world = world();
+
+        // Two bags inside each other.
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((1, 1)));
+        let item = || (Name::new(""), Item::new(Flags::A));
+        let a = world.spawn((grid(), item())).id();
+        let b = world.spawn((grid(), item())).id();
+        world.entity_mut(a).insert(InContainer {
+            container: b,
+            slot: 0,
+        });
+        world.entity_mut(b).insert(InContainer {
+            container: a,
+            slot: 0,
+        });
+
+        let mut state = state(&mut world);
+        let storage = state.get_mut(&mut world).unwrap();
+
+        let walk = storage.walk(a).map(|w| (w.id, w.path)).collect_vec();
+        assert_eq!(walk, [(b, vec![a]), (a, vec![a, b])]);
+        assert!(storage.contains(a, a));
+    }
+
+    #[test]
+    fn ancestors() {
+        let mut world = world();
+
+        let bag = world.spawn_empty().id();
+        let section = world.spawn(SectionOf(bag)).id();
+        let pouch = world
+            .spawn(InContainer {
+                container: section,
+                slot: 2,
+            })
+            .id();
+        let stone = world
+            .spawn(InContainer {
+                container: pouch,
+                slot: 0,
+            })
+            .id();
+
+        let mut state = state(&mut world);
+        let storage = state.get_mut(&mut world).unwrap();
+
+        assert_eq!(storage.parent(pouch), Some(section));
+        assert_eq!(storage.parent(bag), None);
+        assert_eq!(storage.path(stone), [bag, section, pouch]);
+        assert!(storage.ancestors(stone).any(|a| a == bag));
+    }
+}
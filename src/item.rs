@@ -9,9 +9,18 @@ use crate::*;
 
 /// An item.
 #[derive(Component, Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
 #[reflect(Component)]
 pub struct Item<T> {
     pub rotation: ItemRotation,
+    /// Mirrored left to right, before rotating (see `ItemTransform`). Added after `rotation`, so it defaults to unset in older saves.
+    #[reflect(default)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub mirror: bool,
     /// The shape represents this items dimensions (and filled "slots" in case it is not rectangular).
     pub shape: Shape,
     pub flags: T,
@@ -23,15 +32,16 @@ impl<T> Item<T> {
     pub fn new(flags: T) -> Self {
         Self {
             rotation: Default::default(),
+            mirror: false,
             shape: Shape::new([1, 1], true),
             flags,
         }
     }
 
-    /// Set the item shape and unset its rotation.
+    /// Set the item shape and unset its transform.
     pub fn with_shape(mut self, shape: impl Into<Shape>) -> Self {
         self.shape = shape.into();
-        self.rotation = ItemRotation::None;
+        self.set_transform(ItemTransform::default());
         self
     }
 
@@ -40,13 +50,32 @@ impl<T> Item<T> {
         self
     }
 
-    /// Set the item's rotation and apply it to its shape.
-    pub fn with_rotation(mut self, r: ItemRotation) -> Self {
-        self.rotation = r;
-        self.rotate();
+    /// Set the item's rotation (and no mirroring) and apply it to its shape.
+    pub fn with_rotation(self, r: ItemRotation) -> Self {
+        self.with_transform(r.into())
+    }
+
+    /// Set the item's transform and apply it to its shape.
+    pub fn with_transform(mut self, transform: ItemTransform) -> Self {
+        self.set_transform(transform);
+        self.shape = transform.apply(&self.shape);
         self
     }
 
+    /// The item's rotation and mirroring.
+    pub fn transform(&self) -> ItemTransform {
+        ItemTransform {
+            rotation: self.rotation,
+            mirror: self.mirror,
+        }
+    }
+
+    /// Sets the item's rotation and mirroring without changing its shape.
+    pub fn set_transform(&mut self, transform: ItemTransform) {
+        self.rotation = transform.rotation;
+        self.mirror = transform.mirror;
+    }
+
     /// Size in pixels.
     pub fn size(&self, slot_dim: f32) -> Vec2 {
         (self.shape.size.as_vec2() * slot_dim).as_ref().into()
@@ -93,13 +122,23 @@ impl<T> Item<T> {
                 rect.size() * egui::lerp(1.0..=0.88, drag_scale),
             );
 
+            // Mirror first, then rotate (see `ItemTransform`).
+            let image = if self.mirror {
+                image.uv(Rect::from_min_max(
+                    egui::pos2(1.0, 0.0),
+                    egui::pos2(0.0, 1.0),
+                ))
+            } else {
+                image
+            };
+
             // For non-square shapes, we need to un-rotate the paint_at rect. This seems like a bug in egui...
             match self.rotation {
                 ItemRotation::None => image.paint_at(ui, rect),
-                r @ ItemRotation::R180 => image.rotate(r.angle(), Self::PIVOT).paint_at(ui, rect),
-                r => image
+                r @ (ItemRotation::R90 | ItemRotation::R270) => image
                     .rotate(r.angle(), Self::PIVOT)
                     .paint_at(ui, Rect::from_center_size(rect.center(), rect.size().yx())),
+                r => image.rotate(r.angle(), Self::PIVOT).paint_at(ui, rect),
             };
         }
 
@@ -113,15 +152,16 @@ impl<T> Item<T> {
         slot: usize,
         id: Entity,
         name: &str,
-        drag: Option<&DragItem<T>>,
+        contents: &ContentsStorage<T>,
         icon: TextureId,
         slot_dim: f32,
         ui: &mut Ui,
     ) -> Option<ContentsResponse<T>>
     where
-        T: Clone + std::fmt::Display,
+        T: Accepts,
     {
         let eid = Id::new(id);
+        let drag = contents.drag.as_ref();
         let p = ui.ctx().pointer_latest_pos();
 
         // This was a bug: "being dragged" is false on the frame in which we release the button. This means that if we dragged the item onto itself, it would return a hover and prevent a move.
@@ -179,8 +219,7 @@ impl<T> Item<T> {
                             ui.output_mut(|o| o.cursor_icon = CursorIcon::PointingHand);
                             let response = ui.interact(response.rect, eid, Sense::click_and_drag());
 
-                            let response = response
-                                .on_hover_text_at_pointer(self.hover_text(name, ui.style()));
+                            let response = contents.hover_ui(id, self, name, response);
 
                             if response.double_clicked() {
                                 Some(ContentsResponse::Open(id))
@@ -211,7 +250,7 @@ impl<T> Item<T> {
         }
     }
 
-    fn hover_text(&self, name: &str, style: &Style) -> LayoutJob
+    pub fn hover_text(&self, name: &str, style: &Style) -> LayoutJob
     where
         T: std::fmt::Display,
     {
@@ -226,21 +265,11 @@ impl<T> Item<T> {
             .append_to(&mut job, style, FontSelection::Default, Align::Center);
         job
     }
-
-    // Apply rotation to shape.
-    fn rotate(&mut self) {
-        match self.rotation {
-            ItemRotation::None => (),
-            ItemRotation::R90 => self.shape = self.shape.rotate90(),
-            ItemRotation::R180 => self.shape = self.shape.rotate180(),
-            ItemRotation::R270 => self.shape = self.shape.rotate270(),
-        };
-    }
 }
 
 // Finds the closest edge to the point and extends the point outside the edge by some distance.
 // TODO This treats the item as a rectangle and does not take into account empty slots. See boomerang. This should probably extend a line from the center through the point, to a point outside the shape.
-fn outer_offset(Vec2 { x, y }: Vec2, size: Vec2, d: f32) -> Vec2 {
+pub(crate) fn outer_offset(Vec2 { x, y }: Vec2, size: Vec2, d: f32) -> Vec2 {
     // left/right/top/bottom
     [
         // (distance to edge, new point)
@@ -256,12 +285,18 @@ fn outer_offset(Vec2 { x, y }: Vec2, size: Vec2, d: f32) -> Vec2 {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ItemRotation {
     #[default]
     None,
     R90,
     R180,
     R270,
+    // Hex rotations (see `HexContents`). These share `R180` with the square rotations.
+    R60,
+    R120,
+    R240,
+    R300,
 }
 
 impl ItemRotation {
@@ -293,12 +328,27 @@ impl ItemRotation {
         egui::pos2(0.0, 1.0),
     ];
 
+    /// Increment by 90°. Hex rotations (other than 180°) have no square equivalent, so they start over at 90°.
     pub fn increment(&self) -> Self {
         match self {
             Self::None => Self::R90,
             Self::R90 => Self::R180,
             Self::R180 => Self::R270,
-            _ => Self::None,
+            Self::R270 => Self::None,
+            Self::R60 | Self::R120 | Self::R240 | Self::R300 => Self::R90,
+        }
+    }
+
+    /// Increment by 60° for hex items. Square rotations (other than 180°) have no hex equivalent, so they start over at 60°.
+    pub fn increment_hex(&self) -> Self {
+        match self {
+            Self::None => Self::R60,
+            Self::R60 => Self::R120,
+            Self::R120 => Self::R180,
+            Self::R180 => Self::R240,
+            Self::R240 => Self::R300,
+            Self::R300 => Self::None,
+            Self::R90 | Self::R270 => Self::R60,
         }
     }
 
@@ -308,6 +358,37 @@ impl ItemRotation {
             Self::R90 => 90.0_f32.to_radians(),
             Self::R180 => 180.0_f32.to_radians(),
             Self::R270 => 270.0_f32.to_radians(),
+            Self::R60 => 60.0_f32.to_radians(),
+            Self::R120 => 120.0_f32.to_radians(),
+            Self::R240 => 240.0_f32.to_radians(),
+            Self::R300 => 300.0_f32.to_radians(),
+        }
+    }
+
+    /// The opposite rotation.
+    pub fn inverse(&self) -> Self {
+        match *self {
+            Self::R90 => Self::R270,
+            Self::R270 => Self::R90,
+            Self::R60 => Self::R300,
+            Self::R300 => Self::R60,
+            Self::R120 => Self::R240,
+            Self::R240 => Self::R120,
+            r => r,
+        }
+    }
+
+    /// Rotates `shape`.
+    pub fn apply(&self, shape: &Shape) -> Shape {
+        match *self {
+            Self::None => shape.clone(),
+            Self::R90 => shape.rotate90(),
+            Self::R180 => shape.rotate180(),
+            Self::R270 => shape.rotate270(),
+            Self::R60 => shape.rotate60(),
+            Self::R120 => shape.rotate60().rotate60(),
+            Self::R240 => shape.rotate180().rotate60(),
+            Self::R300 => shape.rotate180().rotate60().rotate60(),
         }
     }
 
@@ -315,12 +396,64 @@ impl ItemRotation {
         Rot2::from_angle(self.angle())
     }
 
-    pub fn uvs(&self) -> &[Pos2; 4] {
+    /// Quad uvs for the rotation. Hex rotations don't map onto a quad.
+    pub fn uvs(&self) -> Option<&[Pos2; 4]> {
         match *self {
-            ItemRotation::None => &Self::R0_UVS,
-            ItemRotation::R90 => &Self::R90_UVS,
-            ItemRotation::R180 => &Self::R180_UVS,
-            ItemRotation::R270 => &Self::R270_UVS,
+            ItemRotation::None => Some(&Self::R0_UVS),
+            ItemRotation::R90 => Some(&Self::R90_UVS),
+            ItemRotation::R180 => Some(&Self::R180_UVS),
+            ItemRotation::R270 => Some(&Self::R270_UVS),
+            _ => None,
+        }
+    }
+}
+
+/// How an item is oriented: mirrored left to right (if `mirror` is set), then rotated. Items store their shape with the transform already applied, and the transform is used to paint the icon to match. Only square rotations can be mirrored: items lose their mirroring when dragged over a `HexContents`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemTransform {
+    pub rotation: ItemRotation,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub mirror: bool,
+}
+
+impl From<ItemRotation> for ItemTransform {
+    fn from(rotation: ItemRotation) -> Self {
+        Self {
+            rotation,
+            mirror: false,
+        }
+    }
+}
+
+impl ItemTransform {
+    /// Transforms `shape`.
+    pub fn apply(&self, shape: &Shape) -> Shape {
+        if self.mirror {
+            self.rotation.apply(&shape.flip_x())
+        } else {
+            self.rotation.apply(shape)
+        }
+    }
+
+    /// Undoes the transform, returning the untransformed shape that `apply` turns into `shape`.
+    pub fn unapply(&self, shape: &Shape) -> Shape {
+        let shape = self.rotation.inverse().apply(shape);
+        if self.mirror {
+            shape.flip_x()
+        } else {
+            shape
+        }
+    }
+
+    /// The transform that mirrors the result of this one left to right. Mirroring after a rotation is the same as mirroring before the opposite rotation.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            rotation: self.rotation.inverse(),
+            mirror: !self.mirror,
         }
     }
 }
@@ -344,4 +477,22 @@ mod tests {
         assert_eq!(gen_uvs(2), ItemRotation::R180_UVS);
         assert_eq!(gen_uvs(3), ItemRotation::R270_UVS);
     }
+
+    #[test]
+    fn transform() {
+        let shape = crate::shape!["##.", "#.."];
+        for rotation in [
+            ItemRotation::None,
+            ItemRotation::R90,
+            ItemRotation::R180,
+            ItemRotation::R270,
+        ] {
+            for mirror in [false, true] {
+                let t = ItemTransform { rotation, mirror };
+                assert_eq!(t.mirrored().apply(&shape), t.apply(&shape).flip_x());
+                assert_eq!(t.mirrored().mirrored(), t);
+                assert_eq!(t.unapply(&t.apply(&shape)), shape);
+            }
+        }
+    }
 }
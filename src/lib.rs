@@ -3,12 +3,22 @@ mod events;
 mod icon;
 mod item;
 mod min_frame;
+mod persist;
 mod plugin;
 mod shape;
+mod shape_editor;
+#[cfg(test)]
+mod test_util;
+mod weight;
+mod window;
 
 pub use contents::*;
 pub use events::*;
 pub use icon::*;
 pub use item::*;
+pub use persist::*;
 pub use plugin::RunicPlugin;
 pub use shape::*;
+pub use shape_editor::*;
+pub use weight::*;
+pub use window::*;
@@ -0,0 +1,796 @@
+use std::{any::TypeId, fmt};
+
+use bevy_asset::{uuid::Uuid, AssetPath, AssetServer, LoadFromPath, UntypedHandle};
+use bevy_ecs::{
+    entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+    reflect::AppTypeRegistry,
+    system::SystemState,
+};
+use bevy_reflect::TypeRegistry;
+use bevy_world_serialization::{
+    serde::{DynamicWorldSerializer, WorldDeserializer},
+    DynamicWorld, DynamicWorldBuilder, WorldFilter, WorldInstanceSpawnError,
+};
+use itertools::Itertools;
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Serialize, Serializer,
+};
+
+use crate::*;
+
+/// How inventories are written by `Persist`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    /// Human-readable RON, like Bevy scenes.
+    #[default]
+    Ron,
+    /// Compact binary (postcard). Not self-describing, so it can only be read with the same registered types.
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum PersistError {
+    /// Writing RON failed.
+    Ron(ron::Error),
+    /// Reading RON failed.
+    Parse(ron::error::SpannedError),
+    Binary(postcard::Error),
+    /// The loaded entities couldn't be written to the world, e.g. a component type isn't registered.
+    Spawn(WorldInstanceSpawnError),
+    /// The data was saved by a newer version than `current`.
+    Version {
+        saved: u32,
+        current: u32,
+    },
+    /// Migrated items no longer fit, and there's no overflow container (see `Persist::with_overflow`) or it's full. Nothing is loaded.
+    Overflow(usize),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ron(e) => write!(f, "error writing inventories: {e}"),
+            Self::Parse(e) => write!(f, "error reading inventories: {e}"),
+            Self::Binary(e) => write!(f, "error reading or writing inventories: {e}"),
+            Self::Spawn(e) => write!(f, "error loading inventories: {e}"),
+            Self::Version { saved, current } => write!(
+                f,
+                "inventories were saved with version {saved}, newer than {current}"
+            ),
+            Self::Overflow(n) => write!(f, "{n} migrated items no longer fit"),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+type ItemMigration<T> = Box<dyn Fn(&mut Item<T>) + Send + Sync>;
+type GridMigration<T> = Box<dyn Fn(&mut GridContents<T>) + Send + Sync>;
+type Overflow = Box<dyn Fn(&[Entity]) -> Entity + Send + Sync>;
+
+enum Migration<T> {
+    Item(ItemMigration<T>),
+    Grid(GridMigration<T>),
+}
+
+/// Saves root containers along with all of their items and sections, and loads them back into a world with new entities. Every reflected and registered component on those entities is saved unless denied with `deny_component`. Links from a root to anything outside of what's saved (e.g. the container it's in) are dropped.
+///
+/// Saves carry a version (see `with_version`). Data saved with an older version is changed by the migrations added with `migrate_item` and `migrate_grid`, then the loaded entities are repaired (see `ContentsStorage::repair_subset`): items that no longer fit are moved to the overflow container (see `with_overflow`).
+///
+/// Migrations run on components after they're loaded, so they can't rename or add fields. Fields added to saved components need `#[reflect(default)]` to load older saves.
+pub struct Persist<T> {
+    format: Format,
+    filter: WorldFilter,
+    version: u32,
+    migrations: Vec<(u32, Migration<T>)>,
+    overflow: Option<Overflow>,
+}
+
+impl<T> Default for Persist<T> {
+    fn default() -> Self {
+        Self::new(Format::default())
+    }
+}
+
+impl<T> Persist<T> {
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            filter: WorldFilter::allow_all(),
+            version: 0,
+            migrations: Vec::new(),
+            overflow: None,
+        }
+    }
+
+    pub fn ron() -> Self {
+        Self::new(Format::Ron)
+    }
+
+    pub fn binary() -> Self {
+        Self::new(Format::Binary)
+    }
+
+    /// Don't save component `C`, e.g. audio players or other transient state.
+    pub fn deny_component<C: Component>(mut self) -> Self {
+        self.filter = self.filter.deny::<C>();
+        self
+    }
+
+    /// The version written by `save`, zero by default. Bump it when saved items or containers change, and add migrations for older saves. Loading anything newer is an error.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Changes every item saved before `version`. Migrations run in order of version, then in the order they were added.
+    pub fn migrate_item(
+        mut self,
+        version: u32,
+        f: impl Fn(&mut Item<T>) + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations
+            .push((version, Migration::Item(Box::new(f))));
+        self
+    }
+
+    /// Changes every grid saved before `version`. If the grid is resized (by replacing its shape), items keep their positions.
+    pub fn migrate_grid(
+        mut self,
+        version: u32,
+        f: impl Fn(&mut GridContents<T>) + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations
+            .push((version, Migration::Grid(Box::new(f))));
+        self
+    }
+
+    /// Picks the container for items that no longer fit after migrating, given the loaded roots (e.g. the ground). Without one, or if it's full too, loading fails with `PersistError::Overflow`.
+    pub fn with_overflow(
+        mut self,
+        f: impl Fn(&[Entity]) -> Entity + Send + Sync + 'static,
+    ) -> Self {
+        self.overflow = Some(Box::new(f));
+        self
+    }
+}
+
+impl<T: Accepts> Persist<T> {
+    /// Saves `roots` and everything in them.
+    pub fn save(&self, world: &mut World, roots: &[Entity]) -> Result<Vec<u8>, PersistError> {
+        let mut state = SystemState::<ContentsStorage<T>>::new(world);
+        let storage = state.get_mut(world).unwrap();
+
+        let saved: EntityHashSet = roots
+            .iter()
+            .flat_map(|&root| std::iter::once(root).chain(storage.walk_all(root).map(|(e, _)| e)))
+            .collect();
+
+        // Entities whose container (or owner, for sections) isn't saved.
+        let detached: EntityHashSet = saved
+            .iter()
+            .copied()
+            .filter(|&e| storage.parent(e).is_some_and(|p| !saved.contains(&p)))
+            .collect();
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        let mut scene = DynamicWorldBuilder::from_world(world, &registry)
+            .with_component_filter(self.filter.clone())
+            .deny_all_resources()
+            .extract_entities(saved.iter().copied().sorted())
+            .build();
+
+        let links = [TypeId::of::<InContainer>(), TypeId::of::<SectionOf>()];
+        for entity in scene
+            .entities
+            .iter_mut()
+            .filter(|e| detached.contains(&e.entity))
+        {
+            entity.components.retain(|c| {
+                c.get_represented_type_info()
+                    .is_none_or(|info| !links.contains(&info.type_id()))
+            });
+        }
+
+        let saved = Saved {
+            version: self.version,
+            roots,
+            world: DynamicWorldSerializer::new(&scene, &registry),
+        };
+
+        match self.format {
+            Format::Ron => {
+                let config = ron::ser::PrettyConfig::default().indentor("  ".to_string());
+                ron::ser::to_string_pretty(&saved, config)
+                    .map(String::into_bytes)
+                    .map_err(PersistError::Ron)
+            }
+            Format::Binary => postcard::to_allocvec(&saved).map_err(PersistError::Binary),
+        }
+    }
+
+    /// Loads inventories saved with `save`, spawning new entities for everything. Returns the new roots, in the order they were saved.
+    pub fn load(&self, world: &mut World, data: &[u8]) -> Result<Vec<Entity>, PersistError> {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        let mut assets = Assets(world.get_resource::<AssetServer>().cloned());
+        let seed = SavedDeserializer {
+            registry: &registry,
+            load_from_path: &mut assets,
+        };
+
+        let Loaded {
+            version,
+            roots,
+            world: scene,
+        } = match self.format {
+            Format::Ron => {
+                let mut de = ron::Deserializer::from_bytes(data).map_err(PersistError::Parse)?;
+                let saved = seed
+                    .deserialize(&mut de)
+                    .and_then(|saved| de.end().map(|_| saved))
+                    .map_err(|e| PersistError::Parse(de.span_error(e)))?;
+                saved
+            }
+            Format::Binary => {
+                let mut de = postcard::Deserializer::from_bytes(data);
+                seed.deserialize(&mut de).map_err(PersistError::Binary)?
+            }
+        };
+
+        if version > self.version {
+            return Err(PersistError::Version {
+                saved: version,
+                current: self.version,
+            });
+        }
+
+        let mut map = EntityHashMap::default();
+        scene
+            .write_to_world_with(world, &mut map, &registry)
+            .map_err(PersistError::Spawn)?;
+
+        // Scenes are written without relationship hooks, so `Contains` is rebuilt by inserting each `InContainer` again.
+        for &e in map.values() {
+            if let Some(in_container) = world.get::<InContainer>(e).copied() {
+                world.entity_mut(e).insert(in_container);
+            }
+        }
+
+        let roots = roots
+            .iter()
+            .filter_map(|root| {
+                let e = map.get(root).copied();
+                if e.is_none() {
+                    tracing::warn!("root {root} is missing from the saved inventories");
+                }
+                e
+            })
+            .collect_vec();
+
+        if self.migrations.iter().any(|(v, _)| version < *v) {
+            let loaded = map.values().copied().collect_vec();
+            self.migrate(world, version, &loaded);
+
+            let overflow = self.overflow.as_ref().map(|f| f(&roots));
+            let mut state = SystemState::<ContentsStorage<T>>::new(world);
+            let repaired = state
+                .get_mut(world)
+                .unwrap()
+                .repair_subset(&loaded, overflow);
+            state.apply(world);
+            tracing::debug!(?repaired, "migrated from version {version}");
+
+            if !repaired.detached.is_empty() {
+                // Take back the items moved to the overflow, then mark everything so `on_remove_item` doesn't fire `ItemRemove` for entities the caller never got.
+                let loaded_set: EntityHashSet = loaded.iter().copied().collect();
+                let mut storage = state.get_mut(world).unwrap();
+                for &e in &loaded {
+                    if storage
+                        .in_container
+                        .get(e)
+                        .is_ok_and(|c| !loaded_set.contains(&c.container))
+                    {
+                        storage.unpaint(e);
+                    }
+                }
+                state.apply(world);
+
+                for &e in &loaded {
+                    if let Ok(mut e) = world.get_entity_mut(e) {
+                        e.insert(Detached);
+                    }
+                }
+                for e in loaded {
+                    if let Ok(e) = world.get_entity_mut(e) {
+                        e.despawn();
+                    }
+                }
+                return Err(PersistError::Overflow(repaired.detached.len()));
+            }
+        }
+
+        Ok(roots)
+    }
+
+    // Runs the migrations for `version` on the `loaded` entities.
+    fn migrate(&self, world: &mut World, version: u32, loaded: &[Entity]) {
+        // The original sizes of resized grids.
+        let mut resized = EntityHashMap::default();
+
+        for (_, migration) in self
+            .migrations
+            .iter()
+            .filter(|(v, _)| version < *v)
+            .sorted_by_key(|(v, _)| *v)
+        {
+            for &e in loaded {
+                match migration {
+                    Migration::Item(f) => {
+                        if let Some(mut item) = world.get_mut::<Item<T>>(e) {
+                            f(&mut item);
+                        }
+                    }
+                    Migration::Grid(f) => {
+                        if let Some(mut ci) = world.get_mut::<ContentsItems<T>>(e) {
+                            if let ContentsKind::Grid(grid) = &mut ci.contents {
+                                let size = grid.shape.size;
+                                f(grid);
+                                if grid.shape.size != size {
+                                    resized.entry(e).or_insert(size);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Grid slots are indices, so keep items at the same position in resized grids. Items past the edge get a slot past the end.
+        for &e in loaded {
+            let Some(&InContainer { container, slot }) = world.get::<InContainer>(e) else {
+                continue;
+            };
+            let (Some(old), Some(ContentsKind::Grid(grid))) = (
+                resized.get(&container),
+                world
+                    .get::<ContentsItems<T>>(container)
+                    .map(|ci| &ci.contents),
+            ) else {
+                continue;
+            };
+
+            let width = old.x as usize;
+            let pos = Size::new((slot % width) as u32, (slot / width) as u32);
+            let new_slot = if pos.cmplt(grid.shape.size).all() {
+                grid.shape.slot(pos)
+            } else {
+                grid.shape.area()
+            };
+
+            if new_slot != slot {
+                world.entity_mut(e).insert(InContainer {
+                    container,
+                    slot: new_slot,
+                });
+            }
+        }
+    }
+}
+
+const SAVED_STRUCT: &str = "Inventories";
+const SAVED_VERSION: &str = "version";
+const SAVED_ROOTS: &str = "roots";
+const SAVED_WORLD: &str = "world";
+
+struct Saved<'a> {
+    version: u32,
+    roots: &'a [Entity],
+    world: DynamicWorldSerializer<'a>,
+}
+
+struct Loaded {
+    version: u32,
+    roots: Vec<Entity>,
+    world: DynamicWorld,
+}
+
+impl Serialize for Saved<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct(SAVED_STRUCT, 3)?;
+        state.serialize_field(SAVED_VERSION, &self.version)?;
+        state.serialize_field(SAVED_ROOTS, self.roots)?;
+        state.serialize_field(SAVED_WORLD, &self.world)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SavedField {
+    Version,
+    Roots,
+    World,
+}
+
+struct SavedDeserializer<'a> {
+    registry: &'a TypeRegistry,
+    load_from_path: &'a mut dyn LoadFromPath,
+}
+
+impl<'de> DeserializeSeed<'de> for SavedDeserializer<'_> {
+    type Value = Loaded;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            SAVED_STRUCT,
+            &[SAVED_VERSION, SAVED_ROOTS, SAVED_WORLD],
+            self,
+        )
+    }
+}
+
+impl<'de> Visitor<'de> for SavedDeserializer<'_> {
+    type Value = Loaded;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("saved inventories")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::missing_field(SAVED_VERSION))?;
+        let roots = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::missing_field(SAVED_ROOTS))?;
+        let world = seq
+            .next_element_seed(WorldDeserializer {
+                type_registry: self.registry,
+                load_from_path: self.load_from_path,
+            })?
+            .ok_or_else(|| de::Error::missing_field(SAVED_WORLD))?;
+        Ok(Loaded {
+            version,
+            roots,
+            world,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut version = None;
+        let mut roots = None;
+        let mut world = None;
+        let mut load_from_path = Some(self.load_from_path);
+
+        while let Some(key) = map.next_key()? {
+            match key {
+                SavedField::Version => {
+                    if version.is_some() {
+                        return Err(de::Error::duplicate_field(SAVED_VERSION));
+                    }
+                    version = Some(map.next_value()?);
+                }
+                SavedField::Roots => {
+                    if roots.is_some() {
+                        return Err(de::Error::duplicate_field(SAVED_ROOTS));
+                    }
+                    roots = Some(map.next_value()?);
+                }
+                SavedField::World => {
+                    let Some(load_from_path) = load_from_path.take() else {
+                        return Err(de::Error::duplicate_field(SAVED_WORLD));
+                    };
+                    world = Some(map.next_value_seed(WorldDeserializer {
+                        type_registry: self.registry,
+                        load_from_path,
+                    })?);
+                }
+            }
+        }
+
+        let version = version.ok_or_else(|| de::Error::missing_field(SAVED_VERSION))?;
+        let roots = roots.ok_or_else(|| de::Error::missing_field(SAVED_ROOTS))?;
+        let world = world.ok_or_else(|| de::Error::missing_field(SAVED_WORLD))?;
+        Ok(Loaded {
+            version,
+            roots,
+            world,
+        })
+    }
+}
+
+// Loads handles (e.g. `Icon`) with the asset server, if there is one.
+struct Assets(Option<AssetServer>);
+
+impl LoadFromPath for Assets {
+    fn load_from_path_erased(
+        &mut self,
+        type_id: TypeId,
+        path: AssetPath<'static>,
+    ) -> UntypedHandle {
+        match &mut self.0 {
+            Some(server) => server.load_from_path_erased(type_id, path),
+            None => {
+                tracing::warn!(%path, "no asset server to load with");
+                UntypedHandle::Uuid {
+                    type_id,
+                    uuid: Uuid::nil(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use bevy_reflect::Reflect;
+
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn save_and_load() {
+        let mut app = App::new();
+        app.add_plugins(RunicPlugin::<Flags>::default())
+            .register_type::<Name>();
+        let world = app.world_mut();
+
+        let grid = || ContentsItems::new(GridContents::<Flags>::new((2, 2)).with_flags(Flags::A));
+        let item = || (Name::new("item"), Item::new(Flags::A));
+        let chest = world.spawn(grid()).id();
+        let section = world.spawn(grid()).id();
+        let bag = world
+            .spawn((grid(), item(), Sections(None, vec![section])))
+            .id();
+        world.entity_mut(section).insert(SectionOf(bag));
+        let pouch = world.spawn((grid(), item(), Weight(1.0))).id();
+        let [stone, potion] = [(); 2].map(|_| world.spawn((item(), Weight(0.5))).id());
+
+        let mut state = state(world);
+        let mut storage = state.get_mut(world).unwrap();
+        storage.insert(chest, bag);
+        storage.insert(bag, pouch);
+        storage.insert(pouch, stone);
+        storage.insert(section, potion);
+        state.apply(world);
+
+        for persist in [Persist::<Flags>::ron(), Persist::binary()] {
+            let data = persist.save(world, &[bag, stone]).unwrap();
+            let roots = persist.load(world, &data).unwrap();
+            assert_eq!(roots.len(), 2);
+
+            let storage = state.get_mut(world).unwrap();
+            let shape = |root| {
+                storage
+                    .walk(root)
+                    .map(|w| (w.slot, w.depth()))
+                    .collect_vec()
+            };
+            assert_eq!(shape(roots[0]), shape(bag));
+            assert_eq!(storage.total_weight(roots[0]), 2.0);
+            assert_eq!(storage.members.get(roots[0]).unwrap().len(), 1);
+
+            // The bag is left outside of the chest. The stone was saved with it, so its copy stays in the copy of the pouch.
+            assert_eq!(storage.parent(roots[0]), None);
+            assert!(storage.contains(roots[0], roots[1]));
+            assert_eq!(storage.slot_items(chest), [SlotItem(0, bag)]);
+
+            let originals = [chest, bag, section, pouch, stone, potion];
+            for w in storage.walk(roots[0]) {
+                assert!(!originals.contains(&w.id));
+            }
+            assert_eq!(storage.validate(), []);
+        }
+    }
+
+    #[test]
+    fn migrate() {
+        let mut app = App::new();
+        app.add_plugins(RunicPlugin::<Flags>::default())
+            .register_type::<Name>();
+        let world = app.world_mut();
+
+        let bag = world
+            .spawn(ContentsItems::new(
+                GridContents::<Flags>::new((2, 2)).with_flags(Flags::A),
+            ))
+            .id();
+        let ground = world
+            .spawn(ContentsItems::new(
+                ListContents::<Flags>::new(4).with_flags(Flags::A | Flags::B),
+            ))
+            .id();
+        let [a, b] = [(); 2].map(|_| world.spawn((Name::new(""), Item::new(Flags::A))).id());
+
+        let mut state = state(world);
+        let mut storage = state.get_mut(world).unwrap();
+        storage.insert(bag, a);
+        storage.insert(bag, b);
+        state.apply(world);
+        // Move `a` to the bottom left.
+        world.entity_mut(a).insert(InContainer {
+            container: bag,
+            slot: 2,
+        });
+        let mut ci = world.get_mut::<ContentsItems<Flags>>(bag).unwrap();
+        let ContentsKind::Grid(g) = &mut ci.contents else {
+            unreachable!()
+        };
+        g.shape = Shape::new((2, 2), false);
+        g.shape.paint(&Shape::new((1, 1), true), 1);
+        g.shape.paint(&Shape::new((1, 1), true), 2);
+
+        let data = Persist::<Flags>::ron().save(world, &[bag]).unwrap();
+
+        // Newer saves can't be loaded.
+        let newer = Persist::<Flags>::ron()
+            .with_version(2)
+            .save(world, &[bag])
+            .unwrap();
+        let err = Persist::<Flags>::ron().with_version(1).load(world, &newer);
+        assert!(matches!(
+            err,
+            Err(PersistError::Version {
+                saved: 2,
+                current: 1
+            })
+        ));
+
+        // The bag is now one column wide and holds items with new flags.
+        let persist = || {
+            Persist::<Flags>::ron()
+                .with_version(1)
+                .migrate_grid(1, |g| {
+                    g.shape = Shape::new((1, 2), false);
+                    g.flags = Flags::B;
+                })
+                .migrate_item(1, |item| item.flags = Flags::B)
+        };
+
+        // `b` no longer fits, and there's nowhere to put it. Nothing is left behind, and nothing fires `ItemRemove`.
+        track_removed(world);
+        let count = world.query::<Entity>().iter(world).count();
+        let err = persist().load(world, &data);
+        assert!(matches!(err, Err(PersistError::Overflow(1))));
+        assert_eq!(world.query::<Entity>().iter(world).count(), count);
+        assert_eq!(world.resource::<Removed>().0, []);
+
+        // The same with a full overflow container.
+        let full = world
+            .spawn(ContentsItems::new(
+                ListContents::<Flags>::new(1).with_flags(Flags::B),
+            ))
+            .id();
+        let rock = world.spawn((Name::new(""), Item::new(Flags::B))).id();
+        let mut storage = state.get_mut(world).unwrap();
+        storage.insert(full, rock);
+        state.apply(world);
+        let count = world.query::<Entity>().iter(world).count();
+        let err = persist().with_overflow(move |_| full).load(world, &data);
+        assert!(matches!(err, Err(PersistError::Overflow(1))));
+        assert_eq!(world.query::<Entity>().iter(world).count(), count);
+        assert_eq!(world.resource::<Removed>().0, []);
+        let storage = state.get_mut(world).unwrap();
+        assert_eq!(storage.slot_items(full), [SlotItem(0, rock)]);
+
+        let roots = persist()
+            .with_overflow(move |_| ground)
+            .load(world, &data)
+            .unwrap();
+
+        let storage = state.get_mut(world).unwrap();
+        // `a` kept its position, `b` is past the edge.
+        let [SlotItem(slot, a)] = storage.slot_items(roots[0])[..] else {
+            panic!()
+        };
+        assert_eq!(slot, 1);
+        assert_eq!(storage.items.get(a).unwrap().1.flags, Flags::B);
+        assert_eq!(storage.slot_items(ground).len(), 1);
+        assert_eq!(storage.validate_subset(&[roots[0], a, ground]), []);
+        // Only the loaded entities are repaired.
+        assert_eq!(storage.validate(), [Problem::Occupancy { container: bag }]);
+    }
+
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Durability {
+        value: u32,
+        // Added after the first save.
+        #[reflect(default)]
+        max: u32,
+    }
+
+    #[test]
+    fn added_field() {
+        let mut app = App::new();
+        app.add_plugins(RunicPlugin::<Flags>::default())
+            .register_type::<Name>()
+            .register_type::<Durability>();
+        let world = app.world_mut();
+
+        let pouch = world
+            .spawn(ContentsItems::new(
+                ListContents::<Flags>::new(2).with_flags(Flags::A | Flags::B),
+            ))
+            .id();
+        let sword = world
+            .spawn((
+                Name::new(""),
+                Item::new(Flags::A).with_transform(ItemTransform::default().mirrored()),
+                Durability { value: 3, max: 5 },
+            ))
+            .id();
+
+        let mut state = state(world);
+        state.get_mut(world).unwrap().insert(pouch, sword);
+        state.apply(world);
+
+        // Save, then drop `max` to get the shape of the first save.
+        let data = Persist::<Flags>::ron().save(world, &[pouch]).unwrap();
+        let data = String::from_utf8(data).unwrap();
+        assert!(data.contains("max: 5"));
+        let old = data.lines().filter(|l| !l.contains("max: 5")).join("\n");
+
+        let persist = Persist::<Flags>::ron()
+            .with_version(1)
+            .migrate_item(1, |item| item.flags = Flags::A | Flags::B);
+        let roots = persist.load(world, old.as_bytes()).unwrap();
+
+        let storage = state.get_mut(world).unwrap();
+        let [SlotItem(_, sword)] = storage.slot_items(roots[0])[..] else {
+            panic!()
+        };
+        assert_eq!(
+            storage.items.get(sword).unwrap().1.flags,
+            Flags::A | Flags::B
+        );
+        let durability = world.get::<Durability>(sword).unwrap();
+        assert_eq!((durability.value, durability.max), (3, 0));
+    }
+
+    #[test]
+    fn custom_contents() {
+        let mut app = App::new();
+        app.add_plugins(RunicPlugin::<Flags>::default())
+            .register_type::<Name>()
+            .register_type::<ListContents<Flags>>()
+            .register_type_data::<ListContents<Flags>, ReflectContents<Flags>>();
+        let world = app.world_mut();
+
+        // Any reflected contents can be custom, including the built-in kinds.
+        let pocket = ListContents::<Flags>::new(2).with_flags(Flags::A);
+        let pocket = world
+            .spawn(ContentsItems::new(ContentsKind::custom(pocket)))
+            .id();
+        let stone = world.spawn((Name::new(""), Item::new(Flags::A))).id();
+
+        let mut state = state(world);
+        let mut storage = state.get_mut(world).unwrap();
+        storage.insert(pocket, stone);
+        state.apply(world);
+
+        for persist in [Persist::<Flags>::ron(), Persist::binary()] {
+            let data = persist.save(world, &[pocket]).unwrap();
+            let roots = persist.load(world, &data).unwrap();
+
+            let storage = state.get_mut(world).unwrap();
+            let ContentsKind::Custom(c) = &storage.get(roots[0]).unwrap().contents else {
+                panic!("not custom");
+            };
+            assert_eq!(c.slots(), 2);
+            assert_eq!(storage.slot_items(roots[0]).len(), 1);
+            assert_eq!(storage.validate(), []);
+        }
+    }
+}
@@ -8,13 +8,24 @@ use crate::*;
 #[derive(Default)]
 pub struct RunicPlugin<T>(PhantomData<T>);
 
-impl<T: Reflect + FromReflect + GetTypeRegistration + TypePath + Typed> Plugin for RunicPlugin<T> {
+impl<T: Accepts + Reflect + FromReflect + GetTypeRegistration + TypePath + Typed> Plugin
+    for RunicPlugin<T>
+{
     fn build(&self, app: &mut App) {
         // TODO: separate options per T?
         app.init_resource::<Options>()
             .register_type::<ContentsItems<T>>()
+            .register_type::<Custom<T>>()
             .register_type::<Sections>()
+            .register_type::<InContainer>()
+            .register_type::<SectionOf>()
             .register_type::<Item<T>>()
-            .register_type::<Icon>();
+            .register_type::<Icon>()
+            .register_type::<Weight>()
+            .register_type::<MaxWeight>()
+            .register_type::<Open>()
+            .add_observer(on_remove_item::<T>)
+            .add_observer(on_container_open)
+            .add_observer(on_container_close);
     }
 }
@@ -9,7 +9,7 @@
 // };
 
 use bevy_egui::egui;
-use bevy_math::UVec2;
+use bevy_math::{URect, UVec2};
 use bevy_reflect::prelude::*;
 
 pub use bevy_math::UVec2 as Size;
@@ -63,6 +63,40 @@ impl Shape {
         }
     }
 
+    /// Builds a shape from rows of `#` (filled) and `.` (empty), e.g. `["##.", "#.."]`. `■` and `□` (as printed by `Display`) work too. Rows must all be the same width, and there must be at least one cell. See `shape!` for shapes checked at compile time.
+    pub fn from_rows<S: AsRef<str>>(rows: impl IntoIterator<Item = S>) -> Result<Self, ShapeError> {
+        let mut width = None;
+        let mut fill = Vec::new();
+
+        for (row, s) in rows.into_iter().enumerate() {
+            let len = fill.len();
+            for (col, c) in s.as_ref().chars().enumerate() {
+                fill.push(match c {
+                    '#' | '■' => true,
+                    '.' | '□' => false,
+                    _ => return Err(ShapeError::Cell { row, col, c }),
+                });
+            }
+
+            let w = fill.len() - len;
+            match width {
+                None if w == 0 => return Err(ShapeError::Empty),
+                _ if w == 0 => return Err(ShapeError::EmptyRow(row)),
+                Some(expected) if w != expected => {
+                    return Err(ShapeError::Ragged {
+                        row,
+                        width: w,
+                        expected,
+                    })
+                }
+                _ => width = Some(w),
+            }
+        }
+
+        let width = width.ok_or(ShapeError::Empty)?;
+        Ok(Self::from_width_slice(width, fill))
+    }
+
     pub fn width(&self) -> usize {
         self.size.x as usize
     }
@@ -146,6 +180,61 @@ impl Shape {
         UVec2::new((slot % self.width()) as u32, (slot / self.width()) as u32)
     }
 
+    /// Resizes the shape, keeping filled cells at the same positions. Cells past the new edges are dropped and new cells are empty.
+    pub fn resize(&mut self, size: impl Into<Size>) {
+        let mut dest = Shape::new(size, false);
+        for slot in self.slots() {
+            let pos = self.pos(slot);
+            if pos.cmplt(dest.size).all() {
+                let slot = dest.slot(pos);
+                dest.fill[slot] = true;
+            }
+        }
+        *self = dest;
+    }
+
+    /// Returns true if all filled cells are joined by their edges. Empty shapes are connected.
+    pub fn is_connected(&self) -> bool {
+        self.components().len() <= 1
+    }
+
+    /// Groups filled slots by the cells they're joined to by edges. Each group is in flood fill order, starting with its lowest slot.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.fill.len()];
+        let mut components = Vec::new();
+
+        for first in self.slots() {
+            if seen[first] {
+                continue;
+            }
+
+            let mut component = Vec::new();
+            let mut stack = vec![first];
+            seen[first] = true;
+
+            while let Some(slot) = stack.pop() {
+                component.push(slot);
+                let UVec2 { x, y } = self.pos(slot);
+                let neighbors = [
+                    (x > 0).then(|| slot - 1),
+                    (x + 1 < self.size.x).then_some(slot + 1),
+                    (y > 0).then(|| slot - self.width()),
+                    (y + 1 < self.size.y).then_some(slot + self.width()),
+                ];
+                for n in neighbors.into_iter().flatten() {
+                    if self.fill[n] && !seen[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        components
+    }
+
     /// Returns an iterator over filled slots.
     pub fn slots(&self) -> impl Iterator<Item = usize> + '_ {
         self.fill
@@ -154,8 +243,9 @@ impl Shape {
             .filter_map(|(i, b)| b.then_some(i))
     }
 
-    pub fn rows(&self) -> impl Iterator<Item = &[bool]> + '_ {
-        self.fill.as_slice().chunks(self.width())
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[bool]> + '_ {
+        // Zero-width shapes have no cells, and so no rows.
+        self.fill.as_slice().chunks(self.width().max(1))
     }
 
     // These are adapted from the image crate: https://github.com/image-rs/image/blob/master/src/imageops/affine.rs.
@@ -204,6 +294,166 @@ impl Shape {
         }
         dest
     }
+
+    /// Mirrors the shape left to right.
+    pub fn flip_x(&self) -> Self {
+        Self {
+            size: self.size,
+            fill: self.rows().flat_map(|r| r.iter().rev()).copied().collect(),
+        }
+    }
+
+    /// Mirrors the shape top to bottom.
+    pub fn flip_y(&self) -> Self {
+        Self {
+            size: self.size,
+            fill: self.rows().rev().flatten().copied().collect(),
+        }
+    }
+
+    /// Rotates 60° clockwise, treating the shape as axial hex coordinates (see `HexContents`). Empty border rows and columns are trimmed since the bounds of a rotated hex shape only depend on its filled cells.
+    pub fn rotate60(&self) -> Self {
+        // Axial (q, r) rotates to (-r, q + r).
+        let cells: Vec<_> = self
+            .slots()
+            .map(|slot| {
+                let UVec2 { x, y } = self.pos(slot);
+                (-(y as i32), x as i32 + y as i32)
+            })
+            .collect();
+
+        let Some(min_q) = cells.iter().map(|c| c.0).min() else {
+            return self.clone();
+        };
+        let min_r = cells.iter().map(|c| c.1).min().unwrap();
+        let max_q = cells.iter().map(|c| c.0).max().unwrap();
+        let max_r = cells.iter().map(|c| c.1).max().unwrap();
+
+        let mut dest = Shape::new(
+            ((max_q - min_q + 1) as u32, (max_r - min_r + 1) as u32),
+            false,
+        );
+        for (q, r) in cells {
+            let slot = dest.slot(UVec2::new((q - min_q) as u32, (r - min_r) as u32));
+            dest.fill[slot] = true;
+        }
+        dest
+    }
+}
+
+// Set operations and free space analysis. Shapes of different sizes are aligned at their top left corners.
+impl Shape {
+    /// Cells filled in either shape. The result is as large as both.
+    pub fn union(&self, other: &Shape) -> Self {
+        let mut dest = Shape::new(self.size.max(other.size), false);
+        dest.paint(self, 0);
+        dest.paint(other, 0);
+        dest
+    }
+
+    /// Cells filled in both shapes. The result is only as large as their overlap.
+    pub fn intersection(&self, other: &Shape) -> Self {
+        let mut dest = Shape::new(self.size.min(other.size), false);
+        for slot in 0..dest.fill.len() {
+            let pos = dest.pos(slot);
+            dest.fill[slot] = self.fill[self.slot(pos)] && other.fill[other.slot(pos)];
+        }
+        dest
+    }
+
+    /// Cells filled in this shape and not in `other`. The result is the size of this shape.
+    pub fn difference(&self, other: &Shape) -> Self {
+        let mut dest = self.clone();
+        for slot in other.slots() {
+            let pos = other.pos(slot);
+            if pos.cmplt(self.size).all() {
+                let slot = dest.slot(pos);
+                dest.fill[slot] = false;
+            }
+        }
+        dest
+    }
+
+    /// The smallest rectangle containing every filled cell, or `None` if the shape is empty. `max` is exclusive.
+    pub fn bounds(&self) -> Option<URect> {
+        self.slots()
+            .map(|slot| self.pos(slot))
+            .fold(None, |bounds: Option<URect>, pos| {
+                Some(match bounds {
+                    Some(b) => URect::from_corners(b.min.min(pos), b.max.max(pos + UVec2::ONE)),
+                    None => URect::from_corners(pos, pos + UVec2::ONE),
+                })
+            })
+    }
+
+    /// Removes empty rows and columns from the edges. Empty shapes are unchanged.
+    pub fn trim(&self) -> Self {
+        let Some(bounds) = self.bounds() else {
+            return self.clone();
+        };
+
+        let width = bounds.width() as usize;
+        let fill = self
+            .rows()
+            .skip(bounds.min.y as usize)
+            .take(bounds.height() as usize)
+            .flat_map(|row| &row[bounds.min.x as usize..][..width])
+            .copied();
+        Self::from_width_slice(width, fill)
+    }
+
+    /// Returns an iterator over slots where `other` fits, in slot order.
+    pub fn fitting_slots<'a>(&'a self, other: &'a Shape) -> impl Iterator<Item = usize> + 'a {
+        (0..self.fill.len()).filter(|slot| self.fits(other, *slot))
+    }
+
+    /// The number of empty cells.
+    pub fn free(&self) -> usize {
+        self.fill.iter().filter(|b| !**b).count()
+    }
+
+    /// The largest rectangle of empty cells, or `None` if the shape is full. Ties go to the first found, top to bottom. `max` is exclusive.
+    pub fn largest_free_rect(&self) -> Option<URect> {
+        let mut heights = vec![0u32; self.width()];
+        let mut best: Option<(u32, URect)> = None;
+
+        for (y, row) in self.rows().enumerate() {
+            for (h, filled) in heights.iter_mut().zip(row) {
+                *h = if *filled { 0 } else { *h + 1 };
+            }
+
+            // Extend each column left and right while the columns are at least as tall.
+            for (x, &h) in heights.iter().enumerate() {
+                if h == 0 {
+                    continue;
+                }
+                let left = heights[..x].iter().rev().take_while(|&&o| o >= h).count();
+                let right = heights[x + 1..].iter().take_while(|&&o| o >= h).count();
+                let width = (left + 1 + right) as u32;
+                if best.is_none_or(|(area, _)| width * h > area) {
+                    let min = UVec2::new((x - left) as u32, y as u32 + 1 - h);
+                    best = Some((
+                        width * h,
+                        URect::from_corners(min, min + UVec2::new(width, h)),
+                    ));
+                }
+            }
+        }
+
+        best.map(|(_, rect)| rect)
+    }
+
+    /// How scattered the empty cells are, from 0 (all in one rectangle, or none at all) toward 1 (no two adjoining). This is the share of empty cells outside the largest free rectangle.
+    pub fn fragmentation(&self) -> f32 {
+        let free = self.free();
+        match self.largest_free_rect() {
+            Some(rect) if free > 0 => {
+                let size = rect.size();
+                1.0 - (size.x * size.y) as f32 / free as f32
+            }
+            _ => 0.0,
+        }
+    }
 }
 
 impl std::fmt::Display for Shape {
@@ -217,6 +467,124 @@ impl std::fmt::Display for Shape {
     }
 }
 
+/// An error building a shape from rows (see `Shape::from_rows`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    /// There are no rows, or the first row has no cells.
+    Empty,
+    /// A row after the first has no cells.
+    EmptyRow(usize),
+    /// A row is a different width than the first.
+    Ragged {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// A cell is something other than `#`/`■` or `.`/`□`.
+    Cell { row: usize, col: usize, c: char },
+}
+
+impl std::fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "shape has no cells"),
+            Self::EmptyRow(row) => write!(f, "shape row {row} is empty"),
+            Self::Ragged {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "shape row {row} is {width} wide, expected {expected} like the first row"
+            ),
+            Self::Cell { row, col, c } => write!(
+                f,
+                "unexpected {c:?} in shape at row {row}, column {col}, expected '#' or '.' (or '■' or '□')"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Parses multi-line art, one row per line (see `Shape::from_rows`). Surrounding whitespace and blank lines are ignored, so the output of `Display` parses back.
+impl std::str::FromStr for Shape {
+    type Err = ShapeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_rows(s.lines().map(str::trim).filter(|l| !l.is_empty()))
+    }
+}
+
+/// Builds a `Shape` from rows, like `Shape::from_rows`, but a typo or a ragged row is a compile error:
+///
+/// ```
+/// let boomerang = runic::shape!["##", "#."];
+/// assert_eq!(boomerang, runic::Shape::from_ones(2, [1, 1, 1, 0]));
+/// ```
+#[macro_export]
+macro_rules! shape {
+    ($($row:literal),+ $(,)?) => {{
+        const _: () = $crate::check_shape_rows(&[$($row),+]);
+        $crate::Shape::from_rows([$($row),+]).unwrap()
+    }};
+}
+
+// Checks the rows given to `shape!` at compile time.
+#[doc(hidden)]
+pub const fn check_shape_rows(rows: &[&str]) {
+    let mut width = 0;
+    let mut i = 0;
+    while i < rows.len() {
+        let row = rows[i].as_bytes();
+        let mut w = 0;
+        let mut j = 0;
+        while j < row.len() {
+            match row[j] {
+                b'#' | b'.' => j += 1,
+                // ■ and □
+                0xE2 if j + 2 < row.len()
+                    && row[j + 1] == 0x96
+                    && (row[j + 2] == 0xA0 || row[j + 2] == 0xA1) =>
+                {
+                    j += 3
+                }
+                _ => panic!("shape cells must be '#' or '.' (or '■' or '□')"),
+            }
+            w += 1;
+        }
+
+        if w == 0 {
+            panic!("shape rows can't be empty");
+        }
+        if i > 0 && w != width {
+            panic!("shape rows must all be the same width");
+        }
+        width = w;
+        i += 1;
+    }
+}
+
+/// Shapes are serialized as rows, e.g. `["##.", "#.."]` (see `Shape::from_rows`).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Shape {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.rows().map(|r| {
+            r.iter()
+                .map(|b| if *b { '#' } else { '.' })
+                .collect::<String>()
+        }))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Shape {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = Vec::<String>::deserialize(deserializer)?;
+        Shape::from_rows(rows).map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<Size> for Shape {
     fn from(size: Size) -> Self {
         Shape::new(size, true)
@@ -246,6 +614,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_comparison)]
     fn fits() {
         let a = Shape::from_ones(4, [1, 1, 0, 0, 1, 1, 0, 0]);
         let b = Shape::from_ones(2, [1, 1, 1, 1]);
@@ -292,4 +661,187 @@ mod tests {
         assert_eq!(a.rotate180(), Shape::from_ones(3, [0, 0, 1]));
         assert_eq!(a.rotate270(), Shape::from_ones(1, [0, 0, 1]));
     }
+
+    #[test]
+    fn from_rows() {
+        assert_eq!(
+            Shape::from_rows(["##.", "#.."]),
+            Ok(Shape::from_ones(3, [1, 1, 0, 1, 0, 0]))
+        );
+        assert_eq!(Shape::from_rows([""; 0]), Err(ShapeError::Empty));
+        assert_eq!(Shape::from_rows([""]), Err(ShapeError::Empty));
+        assert_eq!(Shape::from_rows(["#", ""]), Err(ShapeError::EmptyRow(1)));
+        assert_eq!(
+            Shape::from_rows(["##", "#"]),
+            Err(ShapeError::Ragged {
+                row: 1,
+                width: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Shape::from_rows(["#x"]),
+            Err(ShapeError::Cell {
+                row: 0,
+                col: 1,
+                c: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn resize() {
+        let mut a = shape!["##.", "#.."];
+        a.resize((2, 3));
+        assert_eq!(a, shape!["##", "#.", ".."]);
+        a.resize((1, 1));
+        assert_eq!(a, shape!["#"]);
+    }
+
+    #[test]
+    fn is_connected() {
+        assert!(shape!["##.", ".##"].is_connected());
+        assert!(shape!["...", "..."].is_connected());
+        assert!(shape!["#.#", "###"].rotate90().is_connected());
+        // Corners don't count.
+        assert!(!shape!["#.", ".#"].is_connected());
+        assert!(!shape!["#.#"].is_connected());
+    }
+
+    #[test]
+    fn parse() {
+        let a: Shape = "
+            ##.
+            #..
+        "
+        .parse()
+        .unwrap();
+        assert_eq!(a, shape!["##.", "#.."]);
+        assert_eq!(a, shape!["■■□", "■□□"]);
+        assert_eq!(a.to_string().parse(), Ok(a));
+        assert_eq!(
+            "##\n#".parse::<Shape>().unwrap_err().to_string(),
+            "shape row 1 is 1 wide, expected 2 like the first row"
+        );
+        assert_eq!("".parse::<Shape>(), Err(ShapeError::Empty));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use crate::{GridContents, Item, ItemRotation, Occupancy};
+
+        let item = Item::new(1u8)
+            .with_shape(Shape::from_ones(3, [1, 1, 0, 1, 0, 0]))
+            .with_rotation(ItemRotation::R90);
+        let s = ron::to_string(&item).unwrap();
+        assert_eq!(s, r###"(rotation:R90,shape:["##",".#",".."],flags:1)"###);
+        let de: Item<u8> = ron::from_str(&s).unwrap();
+        assert_eq!((de.transform(), de.shape), (item.transform(), item.shape));
+
+        let grid = GridContents::<u8> {
+            expands: false,
+            inline: false,
+            header: None,
+            shape: Shape::new((2, 1), false),
+            occupancy: Occupancy::new(2),
+            flags: 1,
+        };
+        let s = ron::to_string(&grid).unwrap();
+        assert_eq!(s, "(expands:false,inline:false,shape:[\"..\"],flags:1)");
+        let de: GridContents<u8> = ron::from_str(&s).unwrap();
+        assert_eq!((de.shape, de.occupancy), (grid.shape, grid.occupancy));
+
+        for empty in ["[]", r#"[""]"#] {
+            let err = ron::from_str::<Shape>(empty).unwrap_err();
+            assert!(err.to_string().contains("shape has no cells"), "{err}");
+        }
+
+        let err = ron::from_str::<Shape>(r###"["##", "#"]"###).unwrap_err();
+        assert!(err.to_string().contains("row 1 is 1 wide"), "{err}");
+        assert!(ron::from_str::<ItemRotation>("R45").is_err());
+    }
+
+    #[test]
+    fn set_ops() {
+        let a = shape!["##.", "#.."];
+        let b = shape![".#", "##", "#."];
+        assert_eq!(a.union(&b), shape!["##.", "##.", "#.."]);
+        assert_eq!(a.intersection(&b), shape![".#", "#."]);
+        assert_eq!(a.difference(&b), shape!["#..", "..."]);
+        assert_eq!(b.difference(&a), shape!["..", ".#", "#."]);
+    }
+
+    #[test]
+    fn bounds_and_trim() {
+        let a = shape!["....", ".#..", ".##.", "...."];
+        assert_eq!(a.bounds(), Some(URect::new(1, 1, 3, 3)));
+        assert_eq!(a.trim(), shape!["#.", "##"]);
+
+        let empty = Shape::new((2, 2), false);
+        assert_eq!(empty.bounds(), None);
+        assert_eq!(empty.trim(), empty);
+    }
+
+    #[test]
+    fn components() {
+        let a = shape!["#.#", "#.#", "..#"];
+        assert_eq!(a.components(), [vec![0, 3], vec![2, 5, 8]]);
+        assert!(!a.is_connected());
+        assert!(shape!["##", ".#"].is_connected());
+    }
+
+    #[test]
+    fn fitting_slots() {
+        let a = shape!["#...", "...#", "#..."];
+        let b = shape!["##"];
+        itertools::assert_equal(a.fitting_slots(&b), [1, 2, 4, 5, 9, 10]);
+    }
+
+    #[test]
+    fn free_space() {
+        let a = shape!["#...", "...#", "#..."];
+        assert_eq!(a.free(), 9);
+        assert_eq!(a.largest_free_rect(), Some(URect::new(1, 0, 3, 3)));
+        assert_eq!(a.fragmentation(), 1.0 - 6.0 / 9.0);
+
+        assert_eq!(Shape::new((2, 2), true).largest_free_rect(), None);
+        assert_eq!(Shape::new((2, 2), true).fragmentation(), 0.0);
+        assert_eq!(Shape::new((2, 2), false).fragmentation(), 0.0);
+    }
+
+    #[test]
+    fn flip() {
+        let a = shape!["##.", "#.."];
+        assert_eq!(a.flip_x(), shape![".##", "..#"]);
+        assert_eq!(a.flip_y(), shape!["#..", "##."]);
+        assert_eq!(a.flip_x().flip_y(), a.rotate180());
+
+        let empty = Shape {
+            size: Size::ZERO,
+            fill: Vec::new(),
+        };
+        assert_eq!(empty.flip_y(), empty);
+    }
+
+    #[test]
+    fn rotate60() {
+        let a = Shape::from_ones(2, [1, 1, 1, 0]);
+        assert_eq!(a.rotate60(), Shape::from_ones(2, [0, 1, 1, 1]));
+        assert_eq!(a.rotate60().rotate60().rotate60(), a.rotate180());
+
+        let mut b = a.clone();
+        for _ in 0..6 {
+            b = b.rotate60();
+        }
+        assert_eq!(a, b);
+
+        // A line of three rotates onto the diagonal, which is a single column in axial coordinates.
+        let a = Shape::from_ones(3, [1, 1, 1]);
+        assert_eq!(a.rotate60(), Shape::from_ones(1, [1, 1, 1]));
+        assert_eq!(
+            a.rotate60().rotate60(),
+            Shape::from_ones(3, [0, 0, 1, 0, 1, 0, 1, 0, 0])
+        );
+    }
 }
@@ -0,0 +1,220 @@
+use bevy_egui::egui::{
+    DragValue, InnerResponse, Pos2, Rect, Response, Sense, StrokeKind, Ui, Vec2, Widget,
+};
+
+use crate::*;
+
+/// A widget for editing a `Shape`, e.g. an item's shape or a grid's size. Click or drag to toggle cells. The width and height can be changed, keeping filled cells in place. Each rotation is previewed (90° steps, or 60° steps for hex shapes, see `with_hex`), and a warning is shown if the filled cells aren't connected. The response is marked changed when the shape changes.
+pub struct ShapeEditor<'a> {
+    shape: &'a mut Shape,
+    cell_size: f32,
+    max_size: Size,
+    hex: bool,
+}
+
+impl<'a> ShapeEditor<'a> {
+    pub fn new(shape: &'a mut Shape) -> Self {
+        Self {
+            shape,
+            cell_size: 24.0,
+            max_size: Size::splat(16),
+            hex: false,
+        }
+    }
+
+    /// Size of each cell in pixels. Previews are a third of this.
+    pub fn with_cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Largest width and height allowed.
+    pub fn with_max_size(mut self, max_size: impl Into<Size>) -> Self {
+        self.max_size = max_size.into();
+        self
+    }
+
+    /// Edits a shape for `HexContents`. Cells are still edited on a square grid (in axial coordinates), but the six rotations are previewed as hexagons.
+    pub fn with_hex(mut self, hex: bool) -> Self {
+        self.hex = hex;
+        self
+    }
+}
+
+impl Widget for ShapeEditor<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Self {
+            shape,
+            cell_size,
+            max_size,
+            hex,
+        } = self;
+
+        let InnerResponse {
+            inner: changed,
+            mut response,
+        } = ui.vertical(|ui| {
+            let mut size = shape.size;
+            ui.horizontal(|ui| {
+                ui.label("Size:");
+                ui.add(DragValue::new(&mut size.x).range(1..=max_size.x.max(1)));
+                ui.label("×");
+                ui.add(DragValue::new(&mut size.y).range(1..=max_size.y.max(1)));
+            });
+
+            let resized = size != shape.size;
+            if resized {
+                shape.resize(size);
+            }
+
+            let changed = edit_cells(ui, shape, cell_size) || resized;
+
+            let n = (cell_size / 3.0).max(4.0);
+            ui.horizontal(|ui| {
+                if hex {
+                    let mut rotated = shape.clone();
+                    for _ in 0..6 {
+                        hex_preview(ui, &rotated, n);
+                        rotated = rotated.rotate60();
+                    }
+                } else {
+                    for rotated in [
+                        shape.clone(),
+                        shape.rotate90(),
+                        shape.rotate180(),
+                        shape.rotate270(),
+                    ] {
+                        preview(ui, &rotated, n);
+                    }
+                }
+            });
+
+            if !shape.is_connected() {
+                ui.colored_label(ui.visuals().warn_fg_color, "⚠ Some cells aren't connected.");
+            }
+
+            changed
+        });
+
+        if changed {
+            response.mark_changed();
+        }
+        response
+    }
+}
+
+// Shows the cells and toggles them with the pointer. Dragging paints the opposite of the first cell dragged over. Returns true if any cell changed.
+fn edit_cells(ui: &mut Ui, shape: &mut Shape, cell_size: f32) -> bool {
+    let (rect, response) = ui.allocate_exact_size(
+        Vec2::new(shape.size.x as f32, shape.size.y as f32) * cell_size,
+        Sense::click_and_drag(),
+    );
+
+    let Size { x: w, y: h } = shape.size;
+    let cell = move |pos: Pos2| {
+        let p = (pos - rect.min) / cell_size;
+        (p.x >= 0.0 && p.y >= 0.0 && p.x < w as f32 && p.y < h as f32)
+            .then(|| p.x as usize + p.y as usize * w as usize)
+    };
+
+    let mut changed = false;
+    let id = response.id;
+
+    if response.drag_started() {
+        if let Some(slot) = ui.input(|i| i.pointer.press_origin()).and_then(cell) {
+            let paint = !shape.fill[slot];
+            ui.data_mut(|d| d.insert_temp(id, paint));
+        }
+    }
+
+    let pointer = response.interact_pointer_pos().and_then(cell);
+    if response.clicked() {
+        if let Some(slot) = pointer {
+            shape.fill[slot] = !shape.fill[slot];
+            changed = true;
+        }
+    } else if response.dragged() {
+        if let (Some(slot), Some(paint)) = (pointer, ui.data(|d| d.get_temp::<bool>(id))) {
+            if shape.fill[slot] != paint {
+                shape.fill[slot] = paint;
+                changed = true;
+            }
+        }
+    }
+
+    if ui.is_rect_visible(rect) {
+        let visuals = ui.visuals();
+        let hovered = response.hover_pos().and_then(cell);
+        let painter = ui.painter();
+        for slot in 0..shape.fill.len() {
+            let pos = shape.pos(slot);
+            let min = rect.min + Vec2::new(pos.x as f32, pos.y as f32) * cell_size;
+            let r = Rect::from_min_size(min, Vec2::splat(cell_size)).shrink(1.0);
+            let fill = if shape.fill[slot] {
+                visuals.selection.bg_fill
+            } else {
+                visuals.extreme_bg_color
+            };
+            let stroke = if hovered == Some(slot) {
+                visuals.widgets.hovered.fg_stroke
+            } else {
+                visuals.widgets.noninteractive.bg_stroke
+            };
+            painter.rect(r, 2.0, fill, stroke, StrokeKind::Inside);
+        }
+    }
+
+    changed
+}
+
+// Draws filled cells only, in a frame the size of the shape.
+fn preview(ui: &mut Ui, shape: &Shape, cell_size: f32) {
+    let (rect, _) = ui.allocate_exact_size(
+        Vec2::new(shape.size.x as f32, shape.size.y as f32) * cell_size,
+        Sense::hover(),
+    );
+
+    if ui.is_rect_visible(rect) {
+        let visuals = ui.visuals();
+        let painter = ui.painter();
+        painter.rect_stroke(
+            rect,
+            0.0,
+            visuals.widgets.noninteractive.bg_stroke,
+            StrokeKind::Outside,
+        );
+        for slot in shape.slots() {
+            let pos = shape.pos(slot);
+            let min = rect.min + Vec2::new(pos.x as f32, pos.y as f32) * cell_size;
+            painter.rect_filled(
+                Rect::from_min_size(min, Vec2::splat(cell_size)),
+                0.0,
+                visuals.selection.bg_fill,
+            );
+        }
+    }
+}
+
+// Draws filled cells as hexagons, like `HexContents` draws items.
+fn hex_preview(ui: &mut Ui, shape: &Shape, cell_size: f32) {
+    let (rect, _) = ui.allocate_exact_size(item_size(shape, cell_size), Sense::hover());
+
+    if ui.is_rect_visible(rect) {
+        let visuals = ui.visuals();
+        let painter = ui.painter();
+        painter.rect_stroke(
+            rect,
+            0.0,
+            visuals.widgets.noninteractive.bg_stroke,
+            StrokeKind::Outside,
+        );
+        let origin = rect.min + Vec2::new(cell_size / 2.0, cell_size / SQRT_3);
+        painter.add(hex_mesh(
+            shape,
+            rect,
+            origin,
+            visuals.selection.bg_fill,
+            cell_size,
+        ));
+    }
+}
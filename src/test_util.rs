@@ -0,0 +1,54 @@
+// Fixtures shared by the tests.
+
+use bevy_ecs::{prelude::*, system::SystemState};
+use bevy_reflect::{Reflect, ReflectDeserialize, ReflectSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+bitflags::bitflags! {
+    // Reflected and serialized so inventories can be saved (see `Persist`).
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+    #[serde(transparent)]
+    #[reflect(opaque)]
+    #[reflect(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Flags: u8 {
+        const A = 1;
+        const B = 2;
+    }
+}
+
+impl std::fmt::Display for Flags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Collects `ItemRemove` events as (container, slot, item).
+#[derive(Resource, Default)]
+pub struct Removed(pub Vec<(Entity, usize, Entity)>);
+
+// Records every `ItemRemove` in `Removed`.
+pub fn track_removed(world: &mut World) {
+    world.init_resource::<Removed>();
+    world.add_observer(|r: On<ItemRemove>, mut removed: ResMut<Removed>| {
+        removed.0.push((r.entity, r.slot, r.item))
+    });
+}
+
+pub type Storage = SystemState<ContentsStorage<'static, 'static, Flags>>;
+
+// A world with the default options.
+pub fn world() -> World {
+    world_with(Options::default())
+}
+
+pub fn world_with(options: Options) -> World {
+    let mut world = World::new();
+    world.insert_resource(options);
+    world
+}
+
+pub fn state(world: &mut World) -> Storage {
+    SystemState::new(world)
+}
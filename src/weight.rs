@@ -0,0 +1,118 @@
+use bevy_ecs::prelude::*;
+use bevy_reflect::*;
+
+use crate::*;
+
+/// Item weight. Items without weight weigh nothing.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Debug)]
+pub struct Weight(pub f32);
+
+/// Maximum total weight of a container's contents, including sections and nested containers.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Debug)]
+pub struct MaxWeight(pub f32);
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// Weight of `id` plus the weight of its contents.
+    pub fn total_weight(&self, id: Entity) -> f32 {
+        self.weights.get(id).map_or(0.0, |w| w.0) + self.contents_weight(id)
+    }
+
+    /// Total weight of the contents (and sections) of `id`, recursively.
+    pub fn contents_weight(&self, id: Entity) -> f32 {
+        self.walk_all(id)
+            .map(|(i, _)| self.weights.get(i).map_or(0.0, |w| w.0))
+            .sum()
+    }
+
+    /// Returns true if item `id` can be added to `container` without exceeding the max weight of the container or any container above it. Containers already holding the item are not checked, since moving within them doesn't change their weight.
+    pub fn fits_weight(&self, container: Entity, id: Entity) -> bool {
+        let weight = self.total_weight(id);
+        std::iter::once(container)
+            .chain(self.ancestors(container))
+            .all(|c| {
+                self.max_weights.get(c).ok().is_none_or(|max| {
+                    self.ancestors(id).any(|a| a == c) || self.contents_weight(c) + weight <= max.0
+                })
+            })
+    }
+
+    /// Weight label for containers with a max weight, e.g. "12.5 / 20 kg".
+    pub fn weight_text(&self, id: Entity) -> Option<String> {
+        let max = self.max_weights.get(id).ok()?.0;
+        // Round to tenths so float error doesn't show.
+        let round = |w: f32| (w * 10.0).round() / 10.0;
+        Some(format!(
+            "{} / {} {}",
+            round(self.contents_weight(id)),
+            round(max),
+            self.options.weight_unit
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::*;
+
+    #[test]
+    fn nested_weight() {
+        let mut world = world();
+
+        let stone = world.spawn(Weight(1.5)).id();
+        let pouch = ContentsItems::new(GridContents::<Flags>::new((2, 2)));
+        let pouch = world.spawn((pouch, Weight(1.0), MaxWeight(2.0))).id();
+        let bag = ContentsItems::new(GridContents::<Flags>::new((4, 4)));
+        let bag = world.spawn((bag, MaxWeight(2.8))).id();
+        world.entity_mut(pouch).insert(InContainer {
+            container: bag,
+            slot: 0,
+        });
+        world.entity_mut(stone).insert(InContainer {
+            container: pouch,
+            slot: 0,
+        });
+        let [rock, pebble, grain] = [3.0, 0.5, 0.2].map(|w| world.spawn(Weight(w)).id());
+
+        let mut state = state(&mut world);
+        let storage = state.get_mut(&mut world).unwrap();
+
+        assert_eq!(storage.total_weight(pouch), 2.5);
+        assert_eq!(storage.contents_weight(bag), 2.5);
+        assert_eq!(storage.weight_text(bag).as_deref(), Some("2.5 / 2.8 kg"));
+        assert_eq!(storage.weight_text(stone), None);
+
+        assert!(!storage.fits_weight(bag, rock));
+        // Fits the pouch, but not the bag it's in.
+        assert!(!storage.fits_weight(pouch, pebble));
+        assert!(storage.fits_weight(pouch, grain));
+        // Already inside.
+        assert!(storage.fits_weight(pouch, stone));
+
+        let item = Item::new(Flags::empty());
+        assert_eq!(storage.find_slot(bag, rock, &item, &None), None);
+        assert_eq!(storage.find_slot(pouch, pebble, &item, &None), None);
+        assert!(storage.find_slot(pouch, grain, &item, &None).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "contents exceed max weight")]
+    fn spawn_max_weight() {
+        let mut world = world();
+
+        let rock = ContentsBuilder::item(Item::new(Flags::A))
+            .with_name(Name::new("rock"))
+            .with_icon(Default::default())
+            .with_weight(3.0);
+        let bag = GridContents::<Flags>::new((2, 2))
+            .with_flags(Flags::A)
+            .builder()
+            .with_max_weight(2.0)
+            .with_items([rock]);
+
+        let mut state = state(&mut world);
+        state.get_mut(&mut world).unwrap().spawn(bag);
+    }
+}
@@ -0,0 +1,136 @@
+use bevy_ecs::prelude::*;
+use bevy_egui::egui;
+use bevy_math::Vec2;
+use bevy_reflect::*;
+use itertools::Itertools;
+
+use crate::*;
+
+/// Offset between cascaded windows (see `Open`).
+pub const CASCADE_OFFSET: f32 = 24.0;
+
+/// Marks a container that's open in a window (see `ContentsStorage::show_windows`). `RunicPlugin` inserts it on `ContainerOpen` and removes it on `ContainerClose`. It's saved with the inventory (see `Persist`), along with the window position.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Open {
+    /// The top left corner of the window. If unset, the window is cascaded from the window of the container it's in, or the previous window.
+    pub pos: Option<Vec2>,
+}
+
+/// Opens the container in a window.
+pub fn on_container_open(open: On<ContainerOpen>, mut commands: Commands) {
+    commands
+        .entity(open.event_target())
+        .try_insert_if_new(Open::default());
+}
+
+/// Closes the container's window.
+pub fn on_container_close(close: On<ContainerClose>, mut commands: Commands) {
+    commands.entity(close.event_target()).try_remove::<Open>();
+}
+
+impl<T: Accepts> ContentsStorage<'_, '_, T> {
+    /// Shows a window for each open container (see `Open`). Window positions are written back with commands, so `Open` is only read here. Closing a window triggers `ContainerClose` for it and the open containers inside it. While dragging, a window under the pointer that's behind some other window is raised.
+    pub fn show_windows(&mut self, ctx: &egui::Context) {
+        let pointer = ctx.pointer_latest_pos().filter(|_| self.drag.is_some());
+        let mut raise = None;
+        let mut last: Option<Vec2> = None;
+
+        let opened = self
+            .open
+            .iter()
+            .map(|(id, open)| (id, *open))
+            .sorted_by_key(|(id, _)| *id);
+        for (id, open) in opened.collect_vec() {
+            let pos = open.pos.unwrap_or_else(|| {
+                self.ancestors(id)
+                    .find_map(|a| self.open.get(a).ok().and_then(|(_, o)| o.pos))
+                    .or(last)
+                    .map_or(Vec2::ZERO, |p| p + Vec2::splat(CASCADE_OFFSET))
+            });
+            last = Some(pos);
+
+            let title = self
+                .items
+                .get(id)
+                .map_or("Contents", |(name, ..)| name.as_str())
+                .to_owned();
+            let mut is_open = true;
+            let Some(window) = egui::Window::new(title)
+                .id(egui::Id::new(("runic_window", id)))
+                .default_pos(egui::pos2(pos.x, pos.y))
+                .resizable(false)
+                .open(&mut is_open)
+                .show(ctx, |ui| {
+                    self.show(id, ui);
+                })
+            else {
+                continue;
+            };
+
+            let rect = window.response.rect;
+            let layer = window.response.layer_id;
+
+            // New windows go on top. Otherwise, remember where it is.
+            if open.pos.is_none() {
+                ctx.move_to_top(layer);
+            }
+            let pos = Some(Vec2::new(rect.min.x, rect.min.y));
+            if open.pos != pos {
+                self.commands.entity(id).try_insert(Open { pos });
+            }
+
+            if pointer.is_some_and(|p| rect.contains(p)) {
+                raise = Some(layer);
+            }
+
+            if !is_open {
+                let inside = self.walk_items(id).filter(|i| self.open.contains(*i));
+                for c in std::iter::once(id).chain(inside).collect_vec() {
+                    self.commands.trigger(ContainerClose(c));
+                }
+            }
+        }
+
+        // Only raise over other windows (e.g. contents shown in the app's own windows), not between container windows.
+        if let (Some(p), Some(layer)) = (pointer, raise) {
+            let top = ctx.layer_id_at(p);
+            let ours = top.is_some_and(|top| {
+                self.open
+                    .iter()
+                    .any(|(id, _)| top.id == egui::Id::new(("runic_window", id)))
+            });
+            if !ours {
+                ctx.move_to_top(layer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_and_close() {
+        let mut world = World::new();
+        world.add_observer(on_container_open);
+        world.add_observer(on_container_close);
+
+        let pos = Some(Vec2::new(10.0, 20.0));
+        let bag = world.spawn(Open { pos }).id();
+        let pouch = world.spawn_empty().id();
+
+        // Opening again keeps the position.
+        world.trigger(ContainerOpen(bag));
+        world.trigger(ContainerOpen(pouch));
+        world.flush();
+        assert_eq!(world.get::<Open>(bag), Some(&Open { pos }));
+        assert_eq!(world.get::<Open>(pouch), Some(&Open::default()));
+
+        world.trigger(ContainerClose(bag));
+        world.flush();
+        assert_eq!(world.get::<Open>(bag), None);
+    }
+}